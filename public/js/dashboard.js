@@ -3,29 +3,6 @@
  * Handles dynamic loading, real-time updates, dark mode, and PR review integration
  */
 
-// Tauri integration - handle external links
-if (window.__TAURI__) {
-  document.addEventListener('click', function(e) {
-    const link = e.target.closest('a[href]');
-    if (!link) return;
-    
-    const href = link.getAttribute('href');
-    if (!href || href.startsWith('#')) return;
-    
-    // Check if external link
-    try {
-      const url = new URL(href, window.location.href);
-      if (url.hostname !== 'localhost' && url.hostname !== '127.0.0.1' && url.hostname !== window.location.hostname) {
-        e.preventDefault();
-        e.stopPropagation();
-        window.__TAURI__.shell.open(url.href);
-        return false;
-      }
-    } catch (e) {}
-  }, true);
-  console.log('Tauri link handler installed');
-}
-
 // Global state
 let dashboardData = null;
 let lastUpdated = null;
@@ -147,6 +124,29 @@ function setupEventListeners() {
 
   // Notification panel
   initNotificationPanel();
+
+  // Refreshes started from the desktop app's tray or Refresh Now
+  listenForShellRefreshes();
+}
+
+/**
+ * Show progress of refreshes run by the desktop app and reload when they finish
+ */
+function listenForShellRefreshes() {
+  if (!window.__TAURI__) return;
+
+  const names = { github: 'GitHub', shortcut: 'Shortcut', todoist: 'Todoist', calendar: 'Calendar', all: 'all services' };
+  window.__TAURI__.event.listen('refresh-progress', ({ payload }) => {
+    const name = names[payload.service] || payload.service;
+    if (payload.status === 'started') {
+      showToast(`Refreshing ${name}...`, 'info');
+    } else if (payload.status === 'finished') {
+      refreshData();
+      showToast(`Refreshed ${name}`, 'success');
+    } else {
+      showToast(`Refreshing ${name} failed: ${payload.error}`, 'error');
+    }
+  });
 }
 
 /**
@@ -368,7 +368,7 @@ async function openNotification(url, notificationId, type) {
   }
 
   if (webUrl) {
-    window.open(webUrl, '_blank');
+    openLink(webUrl);
   }
 
   // Mark as read
@@ -380,7 +380,7 @@ async function openNotification(url, notificationId, type) {
  */
 async function openShortcutNotification(storyId, notificationId) {
   if (storyId) {
-    window.open(`https://app.shortcut.com/story/${storyId}`, '_blank');
+    openLink(`https://app.shortcut.com/story/${storyId}`);
   }
 
   await dismissNotification(null, notificationId, 'shortcut');
@@ -492,6 +492,13 @@ function startAutoRefresh() {
  * Manual refresh trigger
  */
 async function manualRefresh() {
+  if (window.__TAURI__) {
+    // Progress and failures arrive as refresh-progress events; only a refresh
+    // that is already running is reported here
+    window.__TAURI__.invoke('refresh_service', { service: 'all' })
+      .catch(error => error.kind === 'invalid' && showToast(error.message, 'info'));
+    return;
+  }
   showToast('Refreshing data...', 'info');
   await refreshData();
   showToast('Data refreshed!', 'success');
@@ -981,10 +988,15 @@ async function createTodoistTask(content, dueDate = null, priority = 1) {
 }
 
 /**
- * Open external link
+ * Open external link (through the desktop app's URL allowlist under Tauri)
  */
 function openLink(url) {
-  if (url) {
+  if (!url) return;
+  if (window.__TAURI__) {
+    // Rejects with { kind, message } when the link is blocked or can't be opened
+    window.__TAURI__.invoke('open_external', { url })
+      .catch(error => showToast(error.message || 'Could not open link', 'error'));
+  } else {
     window.open(url, '_blank');
   }
 }
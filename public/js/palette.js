@@ -0,0 +1,123 @@
+/**
+ * Command Palette
+ * Window the Tauri shell opens from a global shortcut or the tray. Searches
+ * the shell's index of dashboard items and runs an action on the selected one.
+ */
+
+const KIND_LABELS = { pr: 'PR', story: 'Story', task: 'Task', event: 'Event', notification: 'Notification' };
+const ACTION_LABELS = { open: 'Open', copyPrompt: 'Copy prompt', complete: 'Complete', markRead: 'Mark read' };
+
+const input = document.getElementById('paletteInput');
+const list = document.getElementById('paletteResults');
+const status = document.getElementById('paletteStatus');
+const defaultStatus = status.innerHTML;
+
+let results = [];
+let selected = 0;
+
+function render() {
+  list.replaceChildren(...results.map((entry, i) => {
+    const item = document.createElement('li');
+    item.className = 'palette-item' + (i === selected ? ' selected' : '');
+
+    const kind = document.createElement('span');
+    kind.className = 'palette-kind';
+    kind.textContent = KIND_LABELS[entry.kind] || entry.kind;
+
+    const text = document.createElement('div');
+    text.className = 'palette-text';
+    const title = document.createElement('div');
+    title.className = 'palette-title';
+    title.textContent = entry.title;
+    const subtitle = document.createElement('div');
+    subtitle.className = 'palette-subtitle';
+    subtitle.textContent = entry.subtitle;
+    text.append(title, subtitle);
+
+    const actions = document.createElement('div');
+    actions.className = 'palette-actions';
+    entry.actions.forEach(action => {
+      const button = document.createElement('button');
+      button.type = 'button';
+      button.className = 'btn btn-secondary';
+      button.textContent = ACTION_LABELS[action] || action;
+      button.addEventListener('click', (e) => {
+        e.stopPropagation();
+        run(entry, action);
+      });
+      actions.appendChild(button);
+    });
+
+    item.append(kind, text, actions);
+    item.addEventListener('click', () => run(entry, entry.actions[0]));
+    return item;
+  }));
+  list.children[selected]?.scrollIntoView({ block: 'nearest' });
+}
+
+function showError(message) {
+  status.replaceChildren();
+  const span = document.createElement('span');
+  span.className = 'quick-add-error';
+  span.textContent = message;
+  status.appendChild(span);
+}
+
+// The shell hides the window once the action has been done
+async function run(entry, action) {
+  if (!entry || !entry.actions.includes(action)) return;
+  try {
+    await window.__TAURI__.invoke('run_palette_action', { id: entry.id, action });
+    input.value = '';
+  } catch (error) {
+    showError(error.message);
+  }
+}
+
+if (window.__TAURI__) {
+  const { invoke } = window.__TAURI__;
+  const { appWindow } = window.__TAURI__.window;
+
+  const search = async () => {
+    results = await invoke('search_palette', { query: input.value });
+    selected = 0;
+    status.innerHTML = defaultStatus;
+    render();
+  };
+
+  const close = () => {
+    input.value = '';
+    appWindow.hide();
+  };
+
+  input.addEventListener('input', search);
+
+  document.addEventListener('keydown', (e) => {
+    const entry = results[selected];
+    const modifier = e.metaKey || e.ctrlKey;
+    if (e.key === 'Escape') {
+      close();
+    } else if (e.key === 'ArrowDown' || e.key === 'ArrowUp') {
+      e.preventDefault();
+      const step = e.key === 'ArrowDown' ? 1 : -1;
+      selected = Math.max(0, Math.min(results.length - 1, selected + step));
+      render();
+    } else if (e.key === 'Enter' && modifier) {
+      e.preventDefault();
+      run(entry, entry?.actions.find(action => action === 'complete' || action === 'markRead'));
+    } else if (e.key === 'Enter') {
+      e.preventDefault();
+      run(entry, 'open');
+    } else if (modifier && e.key.toLowerCase() === 'c' && !window.getSelection().toString()) {
+      e.preventDefault();
+      run(entry, 'copyPrompt');
+    }
+  });
+
+  // Clicking elsewhere dismisses it; reopening searches the latest index
+  window.addEventListener('blur', close);
+  window.addEventListener('focus', () => {
+    input.focus();
+    search();
+  });
+}
@@ -0,0 +1,77 @@
+/**
+ * Quick Add
+ * Small window the Tauri shell opens from a global shortcut to add a Todoist
+ * task without opening the dashboard. Enter submits, Escape or clicking away
+ * closes it.
+ */
+
+const PRIORITY_LABELS = { 1: 'Urgent (p1)', 2: 'High (p2)', 3: 'Medium (p3)', 4: 'Normal (p4)' };
+
+const input = document.getElementById('quickAddInput');
+const hints = document.getElementById('quickAddHints');
+
+function renderHints(task) {
+  hints.replaceChildren();
+  const add = (text, className = 'quick-add-hint') => {
+    const span = document.createElement('span');
+    span.className = className;
+    span.textContent = text;
+    hints.appendChild(span);
+  };
+
+  if (task.dueDate || task.dueString) add(`Due ${task.dueDate || task.dueString}`);
+  if (task.priority) add(PRIORITY_LABELS[task.priority]);
+}
+
+function renderError(message) {
+  hints.replaceChildren();
+  const span = document.createElement('span');
+  span.className = 'quick-add-error';
+  span.textContent = message;
+  hints.appendChild(span);
+}
+
+if (window.__TAURI__) {
+  const { invoke } = window.__TAURI__;
+  const { appWindow } = window.__TAURI__.window;
+  let submitting = false;
+
+  const close = () => {
+    input.value = '';
+    hints.replaceChildren();
+    appWindow.hide();
+  };
+
+  input.addEventListener('input', () => {
+    invoke('parse_quick_task', { input: input.value }).then(renderHints);
+  });
+
+  document.getElementById('quickAddForm').addEventListener('submit', async (e) => {
+    e.preventDefault();
+    if (submitting) return;
+    submitting = true;
+    input.disabled = true;
+    try {
+      // The shell hides the window once Todoist has the task
+      await invoke('add_quick_task', { input: input.value });
+      input.value = '';
+      hints.replaceChildren();
+    } catch (error) {
+      renderError(error.message);
+    } finally {
+      submitting = false;
+      input.disabled = false;
+      input.focus();
+    }
+  });
+
+  document.addEventListener('keydown', (e) => {
+    if (e.key === 'Escape') close();
+  });
+
+  // Behave like a launcher: clicking elsewhere dismisses it, reopening starts fresh
+  window.addEventListener('blur', () => {
+    if (!submitting) close();
+  });
+  window.addEventListener('focus', () => input.focus());
+}
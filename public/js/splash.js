@@ -0,0 +1,80 @@
+/**
+ * Splash Screen
+ * Shown by the Tauri shell while the server starts, and when it fails to
+ */
+
+const STARTUP_STEPS = {
+  port: 'Choosing a port…',
+  vault: 'Unlocking API tokens…',
+  node: 'Looking for Node.js…',
+  server: 'Starting server…'
+};
+
+const FAILURE_TITLES = {
+  nodeNotFound: 'Node.js not found',
+  serverNotFound: 'Server files not found',
+  spawnFailed: 'Could not start the server',
+  portBusy: 'Port already in use',
+  crashed: 'The server crashed',
+  timedOut: 'The server did not start in time'
+};
+
+const FAILURE_HINTS = {
+  nodeNotFound: 'Install Node.js 18 or newer, or set nodePath in desktop.json in the config directory, then retry.',
+  serverNotFound: 'The app bundle looks incomplete. Try reinstalling Mission Control.',
+  spawnFailed: 'Check the logs for details, then retry.',
+  portBusy: 'Another program is using the server port. Close it or retry to pick a new one.',
+  crashed: 'The last lines of server output are shown below.',
+  timedOut: 'The server is taking longer than expected. The last lines of output are shown below.'
+};
+
+function renderState(state) {
+  const status = document.getElementById('splashStatus');
+  const progress = document.getElementById('splashProgress');
+  const error = document.getElementById('splashError');
+
+  if (state.state === 'starting') {
+    status.textContent = STARTUP_STEPS[state.step] || 'Starting server…';
+    progress.style.display = 'flex';
+    error.style.display = 'none';
+    return;
+  }
+
+  if (state.state === 'ready') {
+    status.textContent = 'Loading dashboard…';
+    return;
+  }
+
+  // Failed
+  status.textContent = state.restarting ? 'Restarting server…' : 'Mission Control could not start';
+  progress.style.display = state.restarting ? 'flex' : 'none';
+  error.style.display = 'block';
+
+  document.getElementById('splashReason').textContent = FAILURE_TITLES[state.reason] || 'Something went wrong';
+  document.getElementById('splashMessage').textContent = state.message;
+  document.getElementById('splashHint').textContent = FAILURE_HINTS[state.reason] || '';
+
+  const output = document.getElementById('splashOutput');
+  if (state.output && state.output.length > 0) {
+    output.textContent = state.output.join('\n');
+    output.style.display = 'block';
+  } else {
+    output.style.display = 'none';
+  }
+}
+
+if (window.__TAURI__) {
+  const { invoke, event } = window.__TAURI__;
+
+  invoke('server_state').then(renderState);
+  event.listen('server-state', (e) => renderState(e.payload));
+
+  document.getElementById('retryBtn').addEventListener('click', () => {
+    renderState({ state: 'starting' });
+    invoke('retry_server');
+  });
+
+  document.getElementById('openLogsBtn').addEventListener('click', () => {
+    invoke('open_logs').catch((error) => console.error('Failed to open logs:', error.message));
+  });
+}
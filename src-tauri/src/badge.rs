@@ -0,0 +1,129 @@
+use crate::feed::{DashboardData, Feed};
+use crate::settings::{Settings, ShellSettings};
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
+
+// Cheap to recompute, and short enough that toggling a source in settings
+// shows up right away
+const TICK: Duration = Duration::from_secs(5);
+
+const APP_NAME: &str = "Mission Control";
+
+#[derive(Clone, Copy, Default, PartialEq)]
+struct Counts {
+    github: usize,
+    shortcut: usize,
+    overdue: usize,
+}
+
+impl Counts {
+    fn total(&self) -> usize {
+        self.github + self.shortcut + self.overdue
+    }
+}
+
+// Keep the unread counts in the tray tooltip and title, the dock badge and the
+// window title up to date with the latest dashboard data
+pub fn watch(app: &AppHandle) {
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let updates = app.state::<Feed>().subscribe();
+        let mut shown = None;
+        let mut ticker = tokio::time::interval(TICK);
+        loop {
+            ticker.tick().await;
+            let Some(data) = updates.borrow().clone() else {
+                continue;
+            };
+            let counts = count(&data, &app.state::<Settings>().get());
+            if shown != Some(counts) {
+                show(&app, counts);
+                shown = Some(counts);
+            }
+        }
+    });
+}
+
+fn count(data: &DashboardData, settings: &ShellSettings) -> Counts {
+    Counts {
+        github: if settings.badge_github {
+            data.github_notifications.len()
+        } else {
+            0
+        },
+        shortcut: if settings.badge_shortcut {
+            data.shortcut_notifications.len()
+        } else {
+            0
+        },
+        overdue: if settings.badge_todoist {
+            data.todoist_overdue_count
+        } else {
+            0
+        },
+    }
+}
+
+fn show(app: &AppHandle, counts: Counts) {
+    let parts: Vec<String> = [
+        (counts.github, "GitHub"),
+        (counts.shortcut, "Shortcut"),
+        (counts.overdue, "overdue"),
+    ]
+    .iter()
+    .filter(|(count, _)| *count > 0)
+    .map(|(count, label)| format!("{} {}", count, label))
+    .collect();
+
+    let tray = app.tray_handle();
+    if parts.is_empty() {
+        let _ = tray.set_tooltip(APP_NAME);
+    } else {
+        let _ = tray.set_tooltip(&format!("{}: {}", APP_NAME, parts.join(", ")));
+    }
+
+    // Only macOS can show text next to the tray icon, in the menu bar
+    let total = counts.total();
+    let badge = if total > 0 {
+        total.to_string()
+    } else {
+        String::new()
+    };
+    #[cfg(target_os = "macos")]
+    {
+        let _ = tray.set_title(&badge);
+        set_dock_badge(app, &badge);
+    }
+
+    if let Some(window) = app.get_window("main") {
+        let title = if badge.is_empty() {
+            APP_NAME.to_string()
+        } else {
+            format!("{} ({})", APP_NAME, badge)
+        };
+        let _ = window.set_title(&title);
+    }
+}
+
+// The red badge on the Dock icon. Tauri has no API for it, and neither has an
+// equivalent on Windows taskbar buttons or Linux launchers.
+#[cfg(target_os = "macos")]
+fn set_dock_badge(app: &AppHandle, badge: &str) {
+    use cocoa::appkit::NSApp;
+    use cocoa::base::{id, nil};
+    use cocoa::foundation::NSString;
+    use objc::{msg_send, sel, sel_impl};
+
+    let badge = badge.to_string();
+    // AppKit is only safe to touch from the main thread
+    let _ = app.run_on_main_thread(move || unsafe {
+        let label: id = if badge.is_empty() {
+            nil
+        } else {
+            let label: id = NSString::alloc(nil).init_str(&badge);
+            msg_send![label, autorelease]
+        };
+        let tile: id = msg_send![NSApp(), dockTile];
+        let _: () = msg_send![tile, setBadgeLabel: label];
+    });
+}
@@ -0,0 +1,50 @@
+use serde::Serialize;
+use std::fmt;
+use std::io;
+
+// Error returned by every fallible Tauri command. Serializes as
+// `{ "kind": "notAllowed", "message": "..." }` so pages can branch on `kind`
+// and show `message` in a toast.
+#[derive(Debug, Serialize)]
+#[serde(tag = "kind", content = "message", rename_all = "camelCase")]
+pub enum ShellError {
+    // Refused by policy, e.g. a URL outside the allowlist
+    NotAllowed(String),
+    // The OS could not hand something to another program
+    LaunchFailed(String),
+    // The Node.js server is down or did not answer
+    ServerUnavailable(String),
+    // Bad input from the page or a malformed file
+    Invalid(String),
+    Io(String),
+}
+
+impl fmt::Display for ShellError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ShellError::NotAllowed(message)
+            | ShellError::LaunchFailed(message)
+            | ShellError::ServerUnavailable(message)
+            | ShellError::Invalid(message)
+            | ShellError::Io(message) => f.write_str(message),
+        }
+    }
+}
+
+impl From<io::Error> for ShellError {
+    fn from(e: io::Error) -> Self {
+        ShellError::Io(e.to_string())
+    }
+}
+
+impl From<serde_json::Error> for ShellError {
+    fn from(e: serde_json::Error) -> Self {
+        ShellError::Invalid(e.to_string())
+    }
+}
+
+impl From<reqwest::Error> for ShellError {
+    fn from(e: reqwest::Error) -> Self {
+        ShellError::ServerUnavailable(e.to_string())
+    }
+}
@@ -0,0 +1,130 @@
+use crate::error::ShellError;
+use crate::paths::AppPaths;
+use crate::reminders;
+use crate::settings::Settings;
+use std::fs;
+use tauri::{AppHandle, Manager, Url};
+
+// Sites the dashboard links to; subdomains are allowed too
+const DEFAULT_HOSTS: [&str; 3] = ["github.com", "app.shortcut.com", "todoist.com"];
+
+// Open a URL in the default browser if it is https and its host is allowed
+pub fn open(app: &AppHandle, url: &str) -> Result<(), ShellError> {
+    let result = check(url, &allowed_hosts(app)).and_then(|url| {
+        open::that(url.as_str())
+            .map_err(|e| ShellError::LaunchFailed(format!("Could not open {}: {}", url, e)))
+    });
+    if let Err(e) = &result {
+        println!("{}", e);
+    }
+    result
+}
+
+fn check(url: &str, allowed: &[String]) -> Result<Url, ShellError> {
+    let not_allowed =
+        |reason: String| ShellError::NotAllowed(format!("Refused to open {}: {}", url, reason));
+
+    let parsed = Url::parse(url).map_err(|e| not_allowed(e.to_string()))?;
+    if parsed.scheme() != "https" {
+        return Err(not_allowed(format!(
+            "{} links are not allowed",
+            parsed.scheme()
+        )));
+    }
+    let host = parsed
+        .host_str()
+        .ok_or_else(|| not_allowed("no host".to_string()))?
+        .to_ascii_lowercase();
+
+    let permitted = allowed
+        .iter()
+        .any(|domain| host == *domain || host.ends_with(&format!(".{}", domain)));
+    if !permitted {
+        return Err(not_allowed(format!(
+            "{} is not in the allowed hosts (add it to allowedHosts in desktop.json)",
+            host
+        )));
+    }
+    Ok(parsed)
+}
+
+// The defaults, video call services, the hosts of the configured calendar
+// feeds, and whatever the user added to desktop.json
+fn allowed_hosts(app: &AppHandle) -> Vec<String> {
+    let mut hosts: Vec<String> = DEFAULT_HOSTS
+        .iter()
+        .chain(reminders::MEETING_HOSTS.iter())
+        .map(|h| h.to_string())
+        .collect();
+    hosts.extend(calendar_hosts(app));
+    hosts.extend(
+        app.state::<Settings>()
+            .get()
+            .allowed_hosts
+            .into_iter()
+            .map(|h| h.trim().trim_start_matches("*.").to_ascii_lowercase())
+            .filter(|h| !h.is_empty()),
+    );
+    hosts
+}
+
+fn calendar_hosts(app: &AppHandle) -> Vec<String> {
+    let config: serde_json::Value = match fs::read_to_string(app.state::<AppPaths>().config_file())
+        .ok()
+        .and_then(|data| serde_json::from_str(&data).ok())
+    {
+        Some(config) => config,
+        None => return Vec::new(),
+    };
+
+    ["personalIcalUrl", "workIcalUrl"]
+        .iter()
+        .filter_map(|key| config["calendar"][key].as_str())
+        .filter_map(|url| Url::parse(url).ok())
+        .filter_map(|url| url.host_str().map(str::to_ascii_lowercase))
+        .collect()
+}
+
+// Tauri command used by the injected link handler
+#[tauri::command]
+pub fn open_external(app: AppHandle, url: String) -> Result<(), ShellError> {
+    open(&app, &url)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::check;
+
+    fn allowed() -> Vec<String> {
+        vec!["github.com".to_string(), "app.shortcut.com".to_string()]
+    }
+
+    fn opens(url: &str) -> bool {
+        check(url, &allowed()).is_ok()
+    }
+
+    #[test]
+    fn allows_listed_hosts_and_their_subdomains() {
+        assert!(opens("https://github.com/owner/repo/pull/1"));
+        assert!(opens("https://GitHub.com/owner"));
+        assert!(opens("https://gist.github.com/owner/1"));
+        assert!(opens("https://app.shortcut.com/team/story/42"));
+    }
+
+    #[test]
+    fn refuses_other_schemes() {
+        assert!(!opens("http://github.com/owner"));
+        assert!(!opens("file:///etc/passwd"));
+        assert!(!opens("javascript:alert(1)"));
+        assert!(!opens("github.com/owner"));
+    }
+
+    #[test]
+    fn refuses_lookalike_hosts() {
+        assert!(!opens("https://github.com.evil.com/owner"));
+        assert!(!opens("https://evilgithub.com/owner"));
+        assert!(!opens("https://shortcut.com/team"));
+        assert!(!opens("https://github.com@evil.com/owner"));
+        assert!(!opens("https://evil.com/?next=https://github.com"));
+    }
+}
@@ -0,0 +1,166 @@
+use crate::server::{self, ServerProcess, ServerState};
+use crate::sync::{self, SyncState};
+use serde::Deserialize;
+use std::sync::Arc;
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
+use tokio::sync::{watch, Notify};
+
+// Same cadence as the dashboard's own auto-refresh
+const POLL_INTERVAL: Duration = Duration::from_secs(60);
+
+// The parts of GET /api/data the shell reacts to. Rows keep the SQLite column
+// names; anything not listed here is ignored.
+#[derive(Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct DashboardData {
+    pub calendar: Vec<CalendarEvent>,
+    pub shortcut_stories: Vec<ShortcutStory>,
+    pub shortcut_notifications: Vec<ShortcutNotification>,
+    #[serde(rename = "githubPRs")]
+    pub github_prs: Vec<GithubPr>,
+    pub github_notifications: Vec<GithubNotification>,
+    pub todoist_tasks: Vec<TodoistTask>,
+    pub todoist_overdue_count: usize,
+}
+
+// Times are ISO 8601 in UTC, as written by the calendar service
+#[derive(Deserialize)]
+pub struct CalendarEvent {
+    pub uid: String,
+    pub summary: String,
+    pub description: Option<String>,
+    pub start_time: String,
+    pub end_time: Option<String>,
+    pub location: Option<String>,
+    pub calendar_type: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct ShortcutStory {
+    pub story_id: i64,
+    pub name: String,
+}
+
+#[derive(Deserialize)]
+pub struct ShortcutNotification {
+    pub notification_id: String,
+    pub story_id: Option<i64>,
+    pub actor_name: Option<String>,
+    pub message: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct GithubNotification {
+    pub notification_id: String,
+    pub reason: Option<String>,
+    pub subject_title: Option<String>,
+    pub subject_url: Option<String>,
+    pub repository_name: Option<String>,
+    pub repository_owner: Option<String>,
+    pub updated_at: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct GithubPr {
+    pub number: i64,
+    pub title: String,
+    pub repo_owner: Option<String>,
+    pub repo_name: Option<String>,
+    pub html_url: Option<String>,
+    // SQLite booleans arrive as 0 or 1
+    pub review_requested: Option<i64>,
+}
+
+#[derive(Deserialize)]
+pub struct TodoistTask {
+    pub task_id: String,
+    pub content: String,
+    // YYYY-MM-DD
+    pub due_date: Option<String>,
+    pub url: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct Response {
+    data: DashboardData,
+}
+
+// Latest dashboard data, shared by everything in the shell that watches it
+pub struct Feed {
+    data: watch::Sender<Option<Arc<DashboardData>>>,
+    wake: Notify,
+}
+
+impl Feed {
+    pub fn new() -> Self {
+        Feed {
+            data: watch::channel(None).0,
+            wake: Notify::new(),
+        }
+    }
+
+    pub fn latest(&self) -> Option<Arc<DashboardData>> {
+        self.data.borrow().clone()
+    }
+
+    pub fn subscribe(&self) -> watch::Receiver<Option<Arc<DashboardData>>> {
+        self.data.subscribe()
+    }
+
+    // Fetch now instead of waiting for the next poll, e.g. after a refresh
+    pub fn refresh(&self) {
+        self.wake.notify_one();
+    }
+}
+
+// Poll /api/data while the server is up
+pub fn poll(app: &AppHandle) {
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let client = reqwest::Client::new();
+        let feed = app.state::<Feed>();
+        loop {
+            wait_for_server(&app.state::<ServerProcess>()).await;
+
+            match sync::fetch_last_times(&client).await {
+                Ok(times) => app.state::<SyncState>().record(&times),
+                Err(e) => println!("Could not fetch last sync times: {}", e),
+            }
+            match fetch(&client).await {
+                Ok(data) => {
+                    feed.data.send_replace(Some(Arc::new(data)));
+                }
+                Err(e) => println!("Could not fetch dashboard data: {}", e),
+            }
+
+            tokio::select! {
+                _ = tokio::time::sleep(POLL_INTERVAL) => {}
+                _ = feed.wake.notified() => {}
+            }
+        }
+    });
+}
+
+async fn fetch(client: &reqwest::Client) -> Result<DashboardData, reqwest::Error> {
+    let response: Response = client
+        .get(server::url("/api/data"))
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+    Ok(response.data)
+}
+
+async fn wait_for_server(process: &ServerProcess) {
+    let mut states = process.subscribe();
+    loop {
+        if matches!(*states.borrow_and_update(), ServerState::Ready { .. }) {
+            return;
+        }
+        if states.changed().await.is_err() {
+            return;
+        }
+    }
+}
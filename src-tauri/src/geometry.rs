@@ -0,0 +1,242 @@
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::{
+    AppHandle, GlobalWindowEvent, LogicalSize, Manager, Monitor, PhysicalPosition, PhysicalSize,
+    Window, WindowEvent,
+};
+
+// Moving or resizing fires many events; write once things settle
+const SAVE_DELAY: Duration = Duration::from_millis(500);
+
+// Matches the main window in tauri.conf.json
+const DEFAULT_WIDTH: f64 = 1400.0;
+const DEFAULT_HEIGHT: f64 = 900.0;
+
+// At least this much of the window (in physical pixels) must land on a
+// monitor for the saved position to be used as is
+const MIN_VISIBLE: i64 = 100;
+
+// Where the main window was, in physical pixels. The size is the one it
+// returns to when un-maximized.
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Geometry {
+    x: i32,
+    y: i32,
+    width: u32,
+    height: u32,
+    maximized: bool,
+    monitor: Option<String>,
+}
+
+// The main window's geometry, kept in window.json in the config directory
+pub struct WindowState {
+    path: PathBuf,
+    current: Mutex<Option<Geometry>>,
+    saved: Mutex<Option<Geometry>>,
+    // Bumped on every change so only the last scheduled save writes
+    generation: AtomicU64,
+}
+
+impl WindowState {
+    pub fn load(config_dir: &Path) -> Self {
+        let path = config_dir.join("window.json");
+        let saved: Option<Geometry> = fs::read_to_string(&path)
+            .ok()
+            .and_then(|data| serde_json::from_str(&data).ok());
+        WindowState {
+            path,
+            current: Mutex::new(saved.clone()),
+            saved: Mutex::new(saved),
+            generation: AtomicU64::new(0),
+        }
+    }
+
+    // Write the latest geometry if it changed since the last write
+    pub fn flush(&self) {
+        let current = self.current.lock().unwrap().clone();
+        let mut saved = self.saved.lock().unwrap();
+        if current == *saved {
+            return;
+        }
+        if let Some(geometry) = &current {
+            match serde_json::to_string_pretty(geometry) {
+                Ok(data) => {
+                    if let Err(e) = fs::write(&self.path, data) {
+                        println!("Could not save window position: {}", e);
+                        return;
+                    }
+                }
+                Err(e) => println!("Could not save window position: {}", e),
+            }
+        }
+        *saved = current;
+    }
+}
+
+// Put the main window back where it was last time, on the monitor it was on.
+// If that monitor is still there but the window no longer lands on it (the
+// displays were rearranged), center it there. If the monitor is gone (e.g. an
+// external display was unplugged), center it on the primary monitor instead,
+// keeping the size where it fits.
+pub fn restore(app: &AppHandle) {
+    let Some(window) = app.get_window("main") else {
+        return;
+    };
+    let Some(geometry) = app.state::<WindowState>().current.lock().unwrap().clone() else {
+        return;
+    };
+
+    let monitors = window.available_monitors().unwrap_or_default();
+    let visible_on = |monitor: &Monitor| overlap(&geometry, monitor) >= MIN_VISIBLE;
+    let saved_monitor = geometry
+        .monitor
+        .as_ref()
+        .and_then(|name| monitors.iter().find(|monitor| monitor.name() == Some(name)));
+
+    let target = match saved_monitor {
+        Some(monitor) if visible_on(monitor) => None,
+        Some(monitor) => Some(monitor.clone()),
+        // Without a recorded monitor, any screen the window lands on will do
+        None if geometry.monitor.is_none() && monitors.iter().any(visible_on) => None,
+        None => window
+            .primary_monitor()
+            .ok()
+            .flatten()
+            .or_else(|| monitors.first().cloned()),
+    };
+
+    match target {
+        None => {
+            let _ = window.set_size(PhysicalSize::new(geometry.width, geometry.height));
+            let _ = window.set_position(PhysicalPosition::new(geometry.x, geometry.y));
+        }
+        Some(monitor) => {
+            let area = monitor.size();
+            let width = geometry.width.min(area.width);
+            let height = geometry.height.min(area.height);
+            let x = monitor.position().x + ((area.width - width) / 2) as i32;
+            let y = monitor.position().y + ((area.height - height) / 2) as i32;
+            let _ = window.set_size(PhysicalSize::new(width, height));
+            let _ = window.set_position(PhysicalPosition::new(x, y));
+        }
+    }
+
+    if geometry.maximized {
+        let _ = window.maximize();
+    }
+}
+
+// Track the main window's moves and resizes; called for every window event
+pub fn track(event: &GlobalWindowEvent) {
+    let window = event.window();
+    if window.label() != "main" {
+        return;
+    }
+    let app = window.app_handle();
+    let state = app.state::<WindowState>();
+    match event.event() {
+        WindowEvent::Moved(_) | WindowEvent::Resized(_) if record(window, &state) => {
+            schedule_save(&app);
+        }
+        WindowEvent::CloseRequested { .. } => {
+            record(window, &state);
+            state.flush();
+        }
+        _ => {}
+    }
+}
+
+// Forget the saved geometry and put the window back to its default size,
+// centered
+pub fn reset(app: &AppHandle) {
+    let state = app.state::<WindowState>();
+    state.generation.fetch_add(1, Ordering::SeqCst);
+    *state.current.lock().unwrap() = None;
+    *state.saved.lock().unwrap() = None;
+    let _ = fs::remove_file(&state.path);
+
+    if let Some(window) = app.get_window("main") {
+        let _ = window.unmaximize();
+        let _ = window.set_size(LogicalSize::new(DEFAULT_WIDTH, DEFAULT_HEIGHT));
+        let _ = window.center();
+    }
+}
+
+// Returns false when there is nothing worth saving, e.g. while the window is
+// hidden in the tray or minimized
+fn record(window: &Window, state: &WindowState) -> bool {
+    if !window.is_visible().unwrap_or(false) || window.is_minimized().unwrap_or(false) {
+        return false;
+    }
+    let monitor = window
+        .current_monitor()
+        .ok()
+        .flatten()
+        .and_then(|monitor| monitor.name().cloned());
+
+    let mut current = state.current.lock().unwrap();
+    let geometry = if window.is_maximized().unwrap_or(false) {
+        // Keep the un-maximized size and position from before
+        let Some(mut geometry) = current.clone().or_else(|| measure(window)) else {
+            return false;
+        };
+        geometry.maximized = true;
+        geometry.monitor = monitor;
+        geometry
+    } else {
+        let Some(mut geometry) = measure(window) else {
+            return false;
+        };
+        geometry.monitor = monitor;
+        geometry
+    };
+    *current = Some(geometry);
+    true
+}
+
+fn measure(window: &Window) -> Option<Geometry> {
+    let position = window.outer_position().ok()?;
+    let size = window.inner_size().ok()?;
+    Some(Geometry {
+        x: position.x,
+        y: position.y,
+        width: size.width,
+        height: size.height,
+        maximized: false,
+        monitor: None,
+    })
+}
+
+fn schedule_save(app: &AppHandle) {
+    let generation = app
+        .state::<WindowState>()
+        .generation
+        .fetch_add(1, Ordering::SeqCst)
+        + 1;
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        tokio::time::sleep(SAVE_DELAY).await;
+        let state = app.state::<WindowState>();
+        if state.generation.load(Ordering::SeqCst) == generation {
+            state.flush();
+        }
+    });
+}
+
+// Area of the window that falls on the monitor, as the smaller of the two
+// overlapping edges
+fn overlap(geometry: &Geometry, monitor: &Monitor) -> i64 {
+    let (mx, my) = (monitor.position().x as i64, monitor.position().y as i64);
+    let (mw, mh) = (monitor.size().width as i64, monitor.size().height as i64);
+    let (x, y) = (geometry.x as i64, geometry.y as i64);
+    let (w, h) = (geometry.width as i64, geometry.height as i64);
+
+    let across = (x + w).min(mx + mw) - x.max(mx);
+    let down = (y + h).min(my + mh) - y.max(my);
+    across.min(down)
+}
@@ -0,0 +1,172 @@
+use crate::error::ShellError;
+use crate::notify;
+use crate::palette;
+use crate::quickadd;
+use crate::settings::{Settings, ShellSettings};
+use crate::tray;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use tauri::{AppHandle, GlobalShortcutManager, Manager};
+
+// Global shortcuts the shell has registered, by what they do
+pub struct Hotkeys {
+    bound: Mutex<HashMap<&'static str, String>>,
+}
+
+const TOGGLE_WINDOW: &str = "toggle window";
+const QUICK_ADD: &str = "quick add";
+const PALETTE: &str = "open the command palette";
+
+impl Hotkeys {
+    pub fn new() -> Self {
+        Hotkeys {
+            bound: Mutex::new(HashMap::new()),
+        }
+    }
+
+    // Point `name` at a new accelerator, or unbind it when empty. On failure
+    // the previous accelerator stays registered.
+    fn bind<F>(
+        &self,
+        app: &AppHandle,
+        name: &'static str,
+        accelerator: &str,
+        handler: F,
+    ) -> Result<(), ShellError>
+    where
+        F: Fn() + Send + Clone + 'static,
+    {
+        let accelerator = accelerator.trim();
+        let mut bound = self.bound.lock().unwrap();
+        if bound.get(name).map(String::as_str) == Some(accelerator) {
+            return Ok(());
+        }
+        if let Some((other, _)) = bound
+            .iter()
+            .find(|(other, current)| **other != name && current.eq_ignore_ascii_case(accelerator))
+        {
+            return Err(ShellError::NotAllowed(format!(
+                "{} is already the shortcut to {}",
+                accelerator, other
+            )));
+        }
+
+        let mut manager = app.global_shortcut_manager();
+        let previous = bound.remove(name);
+        if let Some(previous) = &previous {
+            let _ = manager.unregister(previous);
+        }
+        if accelerator.is_empty() {
+            return Ok(());
+        }
+
+        match manager.register(accelerator, handler.clone()) {
+            Ok(()) => {
+                bound.insert(name, accelerator.to_string());
+                Ok(())
+            }
+            Err(e) => {
+                if let Some(previous) = previous {
+                    if manager.register(&previous, handler).is_ok() {
+                        bound.insert(name, previous);
+                    }
+                }
+                Err(ShellError::NotAllowed(format!(
+                    "Could not register {} (another app may be using it): {}",
+                    accelerator, e
+                )))
+            }
+        }
+    }
+}
+
+// Register the shortcuts from desktop.json, reporting any that are taken
+pub fn register(app: &AppHandle) {
+    let settings = app.state::<Settings>().get();
+    let results = [
+        bind_toggle(app, &settings.toggle_shortcut),
+        bind_quick_add(app, &settings.quick_add_shortcut),
+        bind_palette(app, &settings.palette_shortcut),
+    ];
+    for e in results.into_iter().filter_map(Result::err) {
+        report(e);
+    }
+}
+
+fn report(e: ShellError) {
+    println!("{}", e);
+    notify::show(
+        "Keyboard shortcut unavailable".to_string(),
+        format!("{}. Pick another under Settings → Desktop App.", e),
+        Vec::new(),
+        |_| {},
+    );
+}
+
+fn bind_toggle(app: &AppHandle, accelerator: &str) -> Result<(), ShellError> {
+    let handle = app.clone();
+    app.state::<Hotkeys>()
+        .bind(app, TOGGLE_WINDOW, accelerator, move || toggle(&handle))
+}
+
+fn bind_quick_add(app: &AppHandle, accelerator: &str) -> Result<(), ShellError> {
+    let handle = app.clone();
+    app.state::<Hotkeys>()
+        .bind(app, QUICK_ADD, accelerator, move || quickadd::show(&handle))
+}
+
+fn bind_palette(app: &AppHandle, accelerator: &str) -> Result<(), ShellError> {
+    let handle = app.clone();
+    app.state::<Hotkeys>()
+        .bind(app, PALETTE, accelerator, move || palette::show(&handle))
+}
+
+// Show and focus the dashboard, or hide it if it is already in front
+fn toggle(app: &AppHandle) {
+    if tray::dashboard_in_front(app) {
+        tray::hide_window(app);
+    } else {
+        tray::show_window(app);
+    }
+}
+
+// Tauri command for the settings page: register the new shortcut first and
+// only save it if that worked
+#[tauri::command]
+pub fn set_toggle_shortcut(
+    app: AppHandle,
+    settings: tauri::State<'_, Settings>,
+    shortcut: String,
+) -> Result<ShellSettings, ShellError> {
+    bind_toggle(&app, &shortcut)?;
+    let mut update = settings.get();
+    update.toggle_shortcut = shortcut.trim().to_string();
+    settings.set(update)?;
+    Ok(settings.get())
+}
+
+#[tauri::command]
+pub fn set_quick_add_shortcut(
+    app: AppHandle,
+    settings: tauri::State<'_, Settings>,
+    shortcut: String,
+) -> Result<ShellSettings, ShellError> {
+    bind_quick_add(&app, &shortcut)?;
+    let mut update = settings.get();
+    update.quick_add_shortcut = shortcut.trim().to_string();
+    settings.set(update)?;
+    Ok(settings.get())
+}
+
+#[tauri::command]
+pub fn set_palette_shortcut(
+    app: AppHandle,
+    settings: tauri::State<'_, Settings>,
+    shortcut: String,
+) -> Result<ShellSettings, ShellError> {
+    bind_palette(&app, &shortcut)?;
+    let mut update = settings.get();
+    update.palette_shortcut = shortcut.trim().to_string();
+    settings.set(update)?;
+    Ok(settings.get())
+}
@@ -0,0 +1,490 @@
+use crate::error::ShellError;
+use crate::paths::{self, AppPaths};
+use crate::server::{self, ServerProcess};
+use crate::startup;
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::env;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+use tauri::{AppHandle, Manager};
+
+// The database server.js opens; preferred when data/ holds several
+const DATABASE_NAME: &str = "mission-control.db";
+
+// SQLite files that belong to a database and must travel with it
+const SIDECARS: [&str; 2] = ["-wal", "-shm"];
+
+// A Docker or dev checkout with something to import
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportSource {
+    root: PathBuf,
+    config: Option<PathBuf>,
+    databases: Vec<PathBuf>,
+}
+
+impl ImportSource {
+    fn inspect(root: &Path) -> Option<ImportSource> {
+        let config = Some(root.join("config/config.json")).filter(|path| path.is_file());
+        let mut databases: Vec<PathBuf> = fs::read_dir(root.join("data"))
+            .map(|entries| {
+                entries
+                    .filter_map(|entry| entry.ok())
+                    .map(|entry| entry.path())
+                    .filter(|path| path.is_file() && path.extension() == Some("db".as_ref()))
+                    .collect()
+            })
+            .unwrap_or_default();
+        databases.sort();
+
+        if config.is_none() && databases.is_empty() {
+            return None;
+        }
+        Some(ImportSource {
+            root: root.to_path_buf(),
+            config,
+            databases,
+        })
+    }
+
+    fn database(&self) -> Option<&PathBuf> {
+        self.databases
+            .iter()
+            .find(|path| path.file_name() == Some(DATABASE_NAME.as_ref()))
+            .or_else(|| self.databases.first())
+    }
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Change {
+    Added,
+    Changed,
+    Removed,
+}
+
+#[derive(Serialize)]
+pub struct KeyChange {
+    key: String,
+    change: Change,
+}
+
+// What an import would do. Values are left out since most of them are tokens.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportPreview {
+    source: ImportSource,
+    database: Option<PathBuf>,
+    changes: Vec<KeyChange>,
+    unchanged: usize,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportResult {
+    backup_dir: Option<PathBuf>,
+    imported: Vec<PathBuf>,
+}
+
+// Docker/dev checkouts worth offering: wherever the server runs from, plus the
+// usual clone locations in the home directory
+#[tauri::command]
+pub fn find_import_sources() -> Vec<ImportSource> {
+    let mut roots = Vec::new();
+    if let Some(exe_dir) = env::current_exe()
+        .ok()
+        .and_then(|p| p.parent().map(Path::to_path_buf))
+    {
+        roots.extend(server::server_roots(&exe_dir));
+    }
+    if let Some(home) = paths::home_dir() {
+        for dir in [
+            "mission-control",
+            "Projects/mission-control",
+            "src/mission-control",
+        ] {
+            roots.push(home.join(dir));
+        }
+    }
+
+    let mut seen = Vec::new();
+    roots
+        .into_iter()
+        .filter_map(|root| root.canonicalize().ok())
+        .filter(|root| {
+            let new = !seen.contains(root);
+            seen.push(root.clone());
+            new
+        })
+        .filter_map(|root| ImportSource::inspect(&root))
+        .collect()
+}
+
+#[tauri::command]
+pub fn preview_import(
+    paths: tauri::State<'_, AppPaths>,
+    process: tauri::State<'_, ServerProcess>,
+    root: String,
+) -> Result<ImportPreview, ShellError> {
+    let source = inspect(&root)?;
+
+    let (changes, unchanged) = match &source.config {
+        Some(config) => {
+            let mut incoming = flatten(&read_json(config)?);
+            let mut current = fs::read_to_string(paths.config_file())
+                .ok()
+                .and_then(|data| serde_json::from_str(&data).ok())
+                .map(|value| flatten(&value))
+                .unwrap_or_default();
+            // Both sides as the server would see them: config.json only has
+            // empty placeholders for the vault's tokens, and the vault keeps
+            // them when an imported config leaves them empty
+            if let Some(vault) = process.vault() {
+                let secrets = vault.secrets();
+                keep_secrets(&mut current, &secrets);
+                keep_secrets(&mut incoming, &secrets);
+            }
+            compare(&incoming, &current)
+        }
+        None => (Vec::new(), 0),
+    };
+
+    Ok(ImportPreview {
+        database: source.database().cloned(),
+        source,
+        changes,
+        unchanged,
+    })
+}
+
+// Back up the current config and database, then swap in the imported copies
+// while the server is stopped. Files are staged next to their destination
+// first so a failed copy leaves everything as it was, and a failed rename
+// puts the backup back.
+#[tauri::command(async)]
+pub fn run_import(app: AppHandle, root: String) -> Result<ImportResult, ShellError> {
+    let source = inspect(&root)?;
+    let paths = app.state::<AppPaths>();
+
+    let mut files = Vec::new();
+    if let Some(config) = &source.config {
+        // Refuse to import something server.js can't read
+        read_json(config)?;
+        files.push((config.clone(), paths.config_file()));
+    }
+    if let Some(database) = source.database() {
+        let target = paths.database();
+        for suffix in SIDECARS {
+            let sidecar = with_suffix(database, suffix);
+            if sidecar.is_file() {
+                files.push((sidecar, with_suffix(&target, suffix)));
+            }
+        }
+        files.push((database.clone(), target));
+    }
+
+    let mut staged = Vec::new();
+    for (from, to) in &files {
+        let temp = with_suffix(to, ".import");
+        if let Err(e) = fs::copy(from, &temp) {
+            discard(&staged);
+            return Err(ShellError::Io(format!(
+                "Could not copy {}: {}",
+                from.display(),
+                e
+            )));
+        }
+        staged.push(temp);
+    }
+
+    let backup_dir = paths.config_dir.join("backups").join(format!(
+        "import-{}",
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0)
+    ));
+
+    let process = app.state::<ServerProcess>();
+    let result = process.restart_around(|| {
+        let backed_up = backup(&paths, &backup_dir).map_err(|e| {
+            discard(&staged);
+            ShellError::Io(format!("Could not back up current files: {}", e))
+        })?;
+
+        // A leftover WAL would be replayed into the imported database
+        if source.database().is_some() {
+            for suffix in SIDECARS {
+                let _ = fs::remove_file(with_suffix(&paths.database(), suffix));
+            }
+        }
+
+        for (index, ((_, to), temp)) in files.iter().zip(&staged).enumerate() {
+            if let Err(e) = fs::rename(temp, to) {
+                discard(&staged[index..]);
+                // Don't leave a new config next to the old database or the reverse
+                let moved: Vec<PathBuf> = files[..index].iter().map(|(_, to)| to.clone()).collect();
+                return Err(ShellError::Io(match restore(&paths, &backup_dir, &moved) {
+                    Ok(()) => format!(
+                        "Could not import {}: {}. Your previous files were put back.",
+                        to.display(),
+                        e
+                    ),
+                    Err(restore_error) => format!(
+                        "Could not import {}: {}, nor put the previous files back: {} (backup in {})",
+                        to.display(),
+                        e,
+                        restore_error,
+                        backup_dir.display()
+                    ),
+                }));
+            }
+        }
+
+        // Imported configs carry plaintext tokens
+        if let Some(vault) = app.state::<ServerProcess>().vault() {
+            vault.refresh(&paths.config_file());
+        }
+
+        Ok(ImportResult {
+            backup_dir: Some(backup_dir.clone()).filter(|_| backed_up),
+            imported: files.iter().map(|(_, to)| to.clone()).collect(),
+        })
+    });
+    startup::watch(&app);
+
+    if let Ok(imported) = &result {
+        println!("Imported {:?} from {:?}", imported.imported, source.root);
+    }
+    result
+}
+
+fn inspect(root: &str) -> Result<ImportSource, ShellError> {
+    ImportSource::inspect(Path::new(root)).ok_or_else(|| {
+        ShellError::Invalid(format!(
+            "No config/config.json or data/*.db found in {}",
+            root
+        ))
+    })
+}
+
+// Fill in stored tokens wherever `config` has them empty or missing
+fn keep_secrets(config: &mut BTreeMap<String, Value>, secrets: &BTreeMap<String, String>) {
+    for (key, value) in secrets {
+        let empty = match config.get(key) {
+            None => true,
+            Some(Value::String(current)) => current.is_empty(),
+            Some(_) => false,
+        };
+        if empty {
+            config.insert(key.clone(), Value::String(value.clone()));
+        }
+    }
+}
+
+// The keys an import would add, change or remove, and how many it leaves alone
+fn compare(
+    incoming: &BTreeMap<String, Value>,
+    current: &BTreeMap<String, Value>,
+) -> (Vec<KeyChange>, usize) {
+    let mut changes = Vec::new();
+    let mut unchanged = 0;
+    for (key, value) in incoming {
+        match current.get(key) {
+            None => changes.push(KeyChange {
+                key: key.clone(),
+                change: Change::Added,
+            }),
+            Some(existing) if existing != value => changes.push(KeyChange {
+                key: key.clone(),
+                change: Change::Changed,
+            }),
+            Some(_) => unchanged += 1,
+        }
+    }
+    for key in current.keys().filter(|key| !incoming.contains_key(*key)) {
+        changes.push(KeyChange {
+            key: key.clone(),
+            change: Change::Removed,
+        });
+    }
+    (changes, unchanged)
+}
+
+fn read_json(path: &Path) -> Result<Value, ShellError> {
+    let data = fs::read_to_string(path)
+        .map_err(|e| ShellError::Io(format!("{}: {}", path.display(), e)))?;
+    serde_json::from_str(&data)
+        .map_err(|e| ShellError::Invalid(format!("{} is not valid JSON: {}", path.display(), e)))
+}
+
+// Nested config objects as dotted keys, e.g. "github.personalAccessToken"
+fn flatten(value: &Value) -> BTreeMap<String, Value> {
+    fn walk(prefix: &str, value: &Value, out: &mut BTreeMap<String, Value>) {
+        match value {
+            Value::Object(map) if !map.is_empty() => {
+                for (key, value) in map {
+                    let key = if prefix.is_empty() {
+                        key.clone()
+                    } else {
+                        format!("{}.{}", prefix, key)
+                    };
+                    walk(&key, value, out);
+                }
+            }
+            _ => {
+                out.insert(prefix.to_string(), value.clone());
+            }
+        }
+    }
+
+    let mut out = BTreeMap::new();
+    walk("", value, &mut out);
+    out
+}
+
+// Copy whatever currently exists into the backup directory. Returns false when
+// there was nothing to back up.
+fn backup(paths: &AppPaths, backup_dir: &Path) -> io::Result<bool> {
+    let mut current = managed_files(paths);
+    current.retain(|path| path.is_file());
+
+    if current.is_empty() {
+        return Ok(false);
+    }
+    fs::create_dir_all(backup_dir)?;
+    for path in current {
+        if let Some(name) = path.file_name() {
+            fs::copy(&path, backup_dir.join(name))?;
+        }
+    }
+    Ok(true)
+}
+
+// Undo a partial import: copy back everything the backup holds and remove
+// imported files that had nothing in their place before
+fn restore(paths: &AppPaths, backup_dir: &Path, moved: &[PathBuf]) -> io::Result<()> {
+    for path in managed_files(paths) {
+        let Some(name) = path.file_name() else {
+            continue;
+        };
+        let saved = backup_dir.join(name);
+        if saved.is_file() {
+            fs::copy(&saved, &path)?;
+        } else if moved.contains(&path) {
+            fs::remove_file(&path)?;
+        }
+    }
+    Ok(())
+}
+
+// The config, database and database sidecars an import replaces
+fn managed_files(paths: &AppPaths) -> Vec<PathBuf> {
+    let database = paths.database();
+    let mut files = vec![paths.config_file(), database.clone()];
+    files.extend(SIDECARS.iter().map(|suffix| with_suffix(&database, suffix)));
+    files
+}
+
+fn discard(staged: &[PathBuf]) {
+    for path in staged {
+        let _ = fs::remove_file(path);
+    }
+}
+
+fn with_suffix(path: &Path, suffix: &str) -> PathBuf {
+    let mut name = path.as_os_str().to_os_string();
+    name.push(suffix);
+    PathBuf::from(name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{compare, flatten, keep_secrets, Change};
+    use serde_json::{json, Value};
+    use std::collections::BTreeMap;
+
+    fn secrets() -> BTreeMap<String, String> {
+        BTreeMap::from([(
+            "github.personalAccessToken".to_string(),
+            "ghp_stored".to_string(),
+        )])
+    }
+
+    fn changes(incoming: Value, current: Value) -> (Vec<(String, Change)>, usize) {
+        let (mut incoming, mut current) = (flatten(&incoming), flatten(&current));
+        keep_secrets(&mut incoming, &secrets());
+        keep_secrets(&mut current, &secrets());
+        let (changes, unchanged) = compare(&incoming, &current);
+        (
+            changes
+                .into_iter()
+                .map(|change| (change.key, change.change))
+                .collect(),
+            unchanged,
+        )
+    }
+
+    #[test]
+    fn flattens_nested_objects_into_dotted_keys() {
+        let flat = flatten(&json!({
+            "isConfigured": true,
+            "github": { "personalAccessToken": "ghp_1", "useGhCli": false },
+            "calendar": {}
+        }));
+        assert_eq!(flat.len(), 4);
+        assert_eq!(flat["isConfigured"], json!(true));
+        assert_eq!(flat["github.personalAccessToken"], json!("ghp_1"));
+        assert_eq!(flat["github.useGhCli"], json!(false));
+        assert_eq!(flat["calendar"], json!({}));
+    }
+
+    #[test]
+    fn reports_added_changed_and_removed_keys() {
+        let (changes, unchanged) = changes(
+            json!({ "isConfigured": true, "todoist": { "apiToken": "new" }, "extra": 1 }),
+            json!({ "isConfigured": true, "todoist": { "apiToken": "old" }, "gone": 2 }),
+        );
+        assert_eq!(
+            changes,
+            vec![
+                ("extra".to_string(), Change::Added),
+                ("todoist.apiToken".to_string(), Change::Changed),
+                ("gone".to_string(), Change::Removed),
+            ]
+        );
+        // isConfigured, and the stored GitHub token on both sides
+        assert_eq!(unchanged, 2);
+    }
+
+    #[test]
+    fn keeps_stored_tokens_an_import_leaves_empty_or_out() {
+        let current = json!({ "github": { "personalAccessToken": "" } });
+        let (changes_empty, _) = changes(
+            json!({ "github": { "personalAccessToken": "" } }),
+            current.clone(),
+        );
+        let (changes_missing, _) = changes(json!({ "isConfigured": true }), current);
+        assert!(changes_empty.is_empty());
+        assert_eq!(
+            changes_missing,
+            vec![("isConfigured".to_string(), Change::Added)]
+        );
+    }
+
+    #[test]
+    fn reports_a_new_token_as_changed() {
+        let (changes, _) = changes(
+            json!({ "github": { "personalAccessToken": "ghp_new" } }),
+            json!({ "github": { "personalAccessToken": "" } }),
+        );
+        assert_eq!(
+            changes,
+            vec![("github.personalAccessToken".to_string(), Change::Changed)]
+        );
+    }
+}
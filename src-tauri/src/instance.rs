@@ -0,0 +1,233 @@
+use crate::notify;
+use serde::{Deserialize, Serialize};
+use std::env;
+use std::fs::{self, OpenOptions};
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
+
+// How long a second launch waits for the running instance to answer
+const HANDOFF_TIMEOUT: Duration = Duration::from_millis(1500);
+
+// Sent back by the running instance so an unrelated process that happens to
+// own a recycled port isn't mistaken for it
+const ACK: &str = "mission-control";
+
+// What a second launch hands to the running instance
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Activation {
+    pub args: Vec<String>,
+    pub cwd: Option<PathBuf>,
+}
+
+impl Activation {
+    fn current() -> Self {
+        Activation {
+            args: env::args().skip(1).collect(),
+            cwd: env::current_dir().ok(),
+        }
+    }
+}
+
+// Held by the first instance for as long as it runs. The lock file records the
+// loopback port other launches use to reach it, and this process's ID.
+pub struct InstanceLock {
+    path: PathBuf,
+    inbox: Arc<Mutex<Inbox>>,
+}
+
+// Later launches are answered from the moment the lock is taken, well before
+// the window exists, so activations wait here until there's an app to show
+enum Inbox {
+    Waiting(Vec<Activation>),
+    Ready(AppHandle),
+}
+
+// Become the running instance, or hand this launch's arguments to the one that
+// already is. Returns None when another instance took over.
+pub fn acquire(config_dir: &Path) -> Option<InstanceLock> {
+    let path = config_dir.join("instance.lock");
+
+    // Two attempts: the second one follows reclaiming a stale lock
+    for _ in 0..2 {
+        if let Some((port, pid)) = read_lock(&path) {
+            match hand_off(port) {
+                Ok(()) => {
+                    println!("Mission Control is already running; activated it instead");
+                    return None;
+                }
+                // create binds the listener before writing the lock, so nothing
+                // listening, or something else answering, means its owner is
+                // gone, whatever now has its PID
+                Err(e)
+                    if !matches!(
+                        e.kind(),
+                        io::ErrorKind::ConnectionRefused | io::ErrorKind::InvalidData
+                    ) && is_alive(pid) =>
+                {
+                    // Still starting up or busy; a second server would fight it
+                    println!(
+                        "Mission Control (PID {}) is running but did not answer: {}. Remove {:?} if it is not.",
+                        pid, e, path
+                    );
+                    notify::alert(
+                        "Mission Control is not responding",
+                        &format!(
+                            "It is already running (PID {}) but did not answer. Quit it and try again, or delete {} if it isn't running.",
+                            pid,
+                            path.display()
+                        ),
+                    );
+                    return None;
+                }
+                Err(e) => {
+                    println!("Reclaiming stale instance lock {:?}: {}", path, e);
+                    let _ = fs::remove_file(&path);
+                }
+            }
+        }
+
+        match create(&path) {
+            Ok(lock) => return Some(lock),
+            // Another launch created it first; go round and hand off to it
+            Err(e) if e.kind() == io::ErrorKind::AlreadyExists => continue,
+            Err(e) => {
+                // Better to risk a duplicate than to refuse to start at all
+                println!("Could not create instance lock {:?}: {}", path, e);
+                return TcpListener::bind("127.0.0.1:0")
+                    .ok()
+                    .map(|listener| InstanceLock::answer(path, listener));
+            }
+        }
+    }
+
+    println!("Could not acquire instance lock {:?}", path);
+    None
+}
+
+// The port and process ID written by create. A lock without both can't be
+// checked, so it counts as stale.
+fn read_lock(path: &Path) -> Option<(u16, u32)> {
+    let contents = fs::read_to_string(path).ok()?;
+    let mut lines = contents.lines();
+    let port = lines.next()?.trim().parse().ok()?;
+    let pid = lines.next()?.trim().parse().ok()?;
+    Some((port, pid))
+}
+
+fn create(path: &Path) -> io::Result<InstanceLock> {
+    let listener = TcpListener::bind("127.0.0.1:0")?;
+    let port = listener.local_addr()?.port();
+
+    // create_new makes this the tie-breaker between simultaneous launches
+    let mut file = OpenOptions::new().write(true).create_new(true).open(path)?;
+    writeln!(file, "{}\n{}", port, std::process::id())?;
+
+    Ok(InstanceLock::answer(path.to_path_buf(), listener))
+}
+
+#[cfg(unix)]
+fn is_alive(pid: u32) -> bool {
+    if pid == std::process::id() {
+        return false;
+    }
+    // Signal 0 only checks; EPERM means it exists but belongs to someone else
+    let result = unsafe { libc::kill(pid as libc::pid_t, 0) };
+    result == 0 || io::Error::last_os_error().raw_os_error() == Some(libc::EPERM)
+}
+
+#[cfg(windows)]
+fn is_alive(pid: u32) -> bool {
+    if pid == std::process::id() {
+        return false;
+    }
+    std::process::Command::new("tasklist")
+        .args(["/FI", &format!("PID eq {}", pid), "/NH", "/FO", "CSV"])
+        .output()
+        .map(|output| String::from_utf8_lossy(&output.stdout).contains(&format!("\"{}\"", pid)))
+        .unwrap_or(false)
+}
+
+fn hand_off(port: u16) -> io::Result<()> {
+    let addr = SocketAddr::from(([127, 0, 0, 1], port));
+    let mut stream = TcpStream::connect_timeout(&addr, HANDOFF_TIMEOUT)?;
+    stream.set_read_timeout(Some(HANDOFF_TIMEOUT))?;
+
+    let message = serde_json::to_string(&Activation::current())?;
+    writeln!(stream, "{}", message)?;
+
+    let mut reply = String::new();
+    BufReader::new(stream).read_line(&mut reply)?;
+    if reply.trim() == ACK {
+        Ok(())
+    } else {
+        Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "port is not owned by Mission Control",
+        ))
+    }
+}
+
+impl InstanceLock {
+    // Start answering later launches right away, so one that arrives while
+    // this instance is still starting isn't taken for a stale lock
+    fn answer(path: PathBuf, listener: TcpListener) -> Self {
+        let inbox = Arc::new(Mutex::new(Inbox::Waiting(Vec::new())));
+        let queue = Arc::clone(&inbox);
+
+        std::thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                match receive(stream) {
+                    Ok(activation) => {
+                        println!("Activated by another launch: {:?}", activation.args);
+                        match &mut *queue.lock().unwrap() {
+                            Inbox::Waiting(pending) => pending.push(activation),
+                            Inbox::Ready(app) => activate(app, activation),
+                        }
+                    }
+                    Err(e) => println!("Ignoring instance connection: {}", e),
+                }
+            }
+        });
+
+        InstanceLock { path, inbox }
+    }
+
+    // Deliver later launches to the app from now on, including any that came
+    // in while it started: bring the main window forward and pass their
+    // arguments on to the pages as a "second-instance" event
+    pub fn listen(&self, app: &AppHandle) {
+        let previous =
+            std::mem::replace(&mut *self.inbox.lock().unwrap(), Inbox::Ready(app.clone()));
+        if let Inbox::Waiting(pending) = previous {
+            for activation in pending {
+                activate(app, activation);
+            }
+        }
+    }
+
+    // Remove the lock file on a clean exit; a crash leaves it for the next
+    // launch to reclaim
+    pub fn release(&self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+fn activate(app: &AppHandle, activation: Activation) {
+    crate::tray::show_window(app);
+    let _ = app.emit_all("second-instance", activation);
+}
+
+fn receive(mut stream: TcpStream) -> io::Result<Activation> {
+    stream.set_read_timeout(Some(HANDOFF_TIMEOUT))?;
+    let mut reader = BufReader::new(stream.try_clone()?);
+    let mut line = String::new();
+    reader.read_line(&mut line)?;
+    let activation = serde_json::from_str(&line)?;
+
+    writeln!(stream, "{}", ACK)?;
+    Ok(activation)
+}
@@ -0,0 +1,205 @@
+use chrono::{DateTime, SecondsFormat, Utc};
+use serde::Serialize;
+use std::collections::VecDeque;
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufRead, BufReader, Read, Write};
+use std::path::{Path, PathBuf};
+use std::process::Child;
+use std::sync::{Arc, Mutex};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+// Rotate server.log once it grows past this size
+const MAX_LOG_BYTES: u64 = 1024 * 1024;
+
+// Number of rotated files to keep (server.log.1 .. server.log.N)
+const LOG_FILES_KEPT: usize = 5;
+
+// Lines kept in memory for the shell to show
+const MEMORY_LINES: usize = 500;
+
+#[derive(Clone, Copy, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Stream {
+    Stdout,
+    Stderr,
+}
+
+impl Stream {
+    fn tag(self) -> &'static str {
+        match self {
+            Stream::Stdout => "stdout",
+            Stream::Stderr => "stderr",
+        }
+    }
+}
+
+#[derive(Clone, Serialize)]
+pub struct LogLine {
+    pub stream: Stream,
+    pub line: String,
+    pub at: u64,
+}
+
+struct RotatingFile {
+    path: PathBuf,
+    file: Option<File>,
+    size: u64,
+}
+
+impl RotatingFile {
+    fn open(path: PathBuf) -> Self {
+        if let Some(dir) = path.parent() {
+            let _ = fs::create_dir_all(dir);
+        }
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .map_err(|e| println!("Failed to open log file {:?}: {}", path, e))
+            .ok();
+        let size = fs::metadata(&path).map(|m| m.len()).unwrap_or(0);
+        RotatingFile { path, file, size }
+    }
+
+    fn write_line(&mut self, line: &str) {
+        if self.size >= MAX_LOG_BYTES {
+            self.rotate();
+        }
+        if let Some(file) = self.file.as_mut() {
+            if writeln!(file, "{}", line).is_ok() {
+                self.size += line.len() as u64 + 1;
+            }
+        }
+    }
+
+    // server.log -> server.log.1 -> ... -> server.log.N (dropped)
+    fn rotate(&mut self) {
+        self.file = None;
+        for index in (1..LOG_FILES_KEPT).rev() {
+            let from = rotated_path(&self.path, index);
+            if from.exists() {
+                let _ = fs::rename(&from, rotated_path(&self.path, index + 1));
+            }
+        }
+        let _ = fs::rename(&self.path, rotated_path(&self.path, 1));
+        *self = RotatingFile::open(self.path.clone());
+    }
+}
+
+fn rotated_path(path: &Path, index: usize) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(format!(".{}", index));
+    PathBuf::from(name)
+}
+
+// Drains the server's stdout/stderr so node never blocks on a full pipe
+pub struct ServerLogs {
+    dir: PathBuf,
+    file: Mutex<RotatingFile>,
+    recent: Mutex<VecDeque<LogLine>>,
+}
+
+impl ServerLogs {
+    pub fn new(dir: PathBuf) -> Arc<Self> {
+        let file = RotatingFile::open(dir.join("server.log"));
+        Arc::new(ServerLogs {
+            dir,
+            file: Mutex::new(file),
+            recent: Mutex::new(VecDeque::with_capacity(MEMORY_LINES)),
+        })
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    // Most recent lines, oldest first
+    pub fn recent(&self, limit: usize) -> Vec<LogLine> {
+        let recent = self.recent.lock().unwrap();
+        let skip = recent.len().saturating_sub(limit);
+        recent.iter().skip(skip).cloned().collect()
+    }
+
+    // Take the child's pipes and start a reader thread for each. `on_stdout`
+    // sees every stdout line so the caller can watch for startup messages.
+    pub fn attach<F>(self: &Arc<Self>, child: &mut Child, on_stdout: F)
+    where
+        F: Fn(&str) + Send + 'static,
+    {
+        if let Some(stdout) = child.stdout.take() {
+            self.drain(stdout, Stream::Stdout, on_stdout);
+        }
+        if let Some(stderr) = child.stderr.take() {
+            self.drain(stderr, Stream::Stderr, |_| {});
+        }
+    }
+
+    fn drain<R, F>(self: &Arc<Self>, pipe: R, stream: Stream, on_line: F)
+    where
+        R: Read + Send + 'static,
+        F: Fn(&str) + Send + 'static,
+    {
+        let logs = Arc::clone(self);
+        std::thread::spawn(move || {
+            let mut reader = BufReader::new(pipe);
+            let mut buffer = Vec::new();
+            loop {
+                buffer.clear();
+                match reader.read_until(b'\n', &mut buffer) {
+                    Ok(0) => break,
+                    Ok(_) => {
+                        let line = String::from_utf8_lossy(&buffer);
+                        let line = line.trim_end_matches(['\r', '\n']);
+                        on_line(line);
+                        logs.push(stream, line);
+                    }
+                    Err(e) => {
+                        println!("Failed to read server {}: {}", stream.tag(), e);
+                        break;
+                    }
+                }
+            }
+        });
+    }
+
+    fn push(&self, stream: Stream, line: &str) {
+        let now = SystemTime::now();
+        self.file.lock().unwrap().write_line(&format!(
+            "{} [{}] {}",
+            format_timestamp(now),
+            stream.tag(),
+            line
+        ));
+
+        if cfg!(debug_assertions) {
+            println!("[server {}] {}", stream.tag(), line);
+        }
+
+        let mut recent = self.recent.lock().unwrap();
+        if recent.len() == MEMORY_LINES {
+            recent.pop_front();
+        }
+        recent.push_back(LogLine {
+            stream,
+            line: line.to_string(),
+            at: now
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_millis() as u64)
+                .unwrap_or(0),
+        });
+    }
+}
+
+// Tauri command to show the tail of the server output
+#[tauri::command]
+pub fn server_logs(
+    process: tauri::State<'_, crate::server::ServerProcess>,
+    limit: Option<usize>,
+) -> Vec<LogLine> {
+    process.logs().recent(limit.unwrap_or(MEMORY_LINES))
+}
+
+// UTC timestamp in RFC 3339 form, e.g. 2024-03-01T09:15:02.123Z
+fn format_timestamp(time: SystemTime) -> String {
+    DateTime::<Utc>::from(time).to_rfc3339_opts(SecondsFormat::Millis, true)
+}
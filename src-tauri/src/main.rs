@@ -3,165 +3,220 @@
     windows_subsystem = "windows"
 )]
 
-use std::process::{Command, Stdio};
-use std::sync::Mutex;
+mod badge;
+mod error;
+mod external;
+mod feed;
+mod geometry;
+mod hotkeys;
+mod import;
+mod instance;
+mod logs;
+mod node;
+mod notifications;
+mod notify;
+mod palette;
+mod paths;
+mod quickadd;
+mod reminders;
+mod server;
+mod settings;
+mod startup;
+mod sync;
+mod tray;
+mod vault;
+
 use tauri::Manager;
 use std::env;
-use std::path::PathBuf;
-
-struct ServerProcess(Mutex<Option<std::process::Child>>);
+use instance::InstanceLock;
+use feed::Feed;
+use geometry::WindowState;
+use hotkeys::Hotkeys;
+use logs::ServerLogs;
+use palette::Palette;
+use paths::AppPaths;
+use server::ServerProcess;
+use settings::Settings;
+use sync::SyncState;
 
-// Tauri command to open external URLs
-#[tauri::command]
-fn open_external(url: String) {
-    let _ = open::that(url);
-}
+// Installed on each server page so external links open in the default browser
+const LINK_HANDLER_JS: &str = r#"
+    (function() {
+        if (window.__TAURI_LINK_HANDLER__) return;
+        window.__TAURI_LINK_HANDLER__ = true;
+        
+        document.addEventListener('click', function(e) {
+            const link = e.target.closest('a[href]');
+            if (!link) return;
+            
+            const href = link.getAttribute('href');
+            if (!href || href.startsWith('#')) return;
+            
+            try {
+                const url = new URL(href, window.location.href);
+                if (url.hostname !== 'localhost' && url.hostname !== '127.0.0.1') {
+                    e.preventDefault();
+                    e.stopPropagation();
+                    // Call Tauri command
+                    window.__TAURI__.invoke('open_external', { url: url.href })
+                        .catch(err => typeof showToast === 'function'
+                            ? showToast(err.message, 'error')
+                            : console.warn('Blocked external link:', err.message));
+                    return false;
+                }
+            } catch (e) {}
+        }, true);
+        
+        console.log('Tauri link handler installed');
+    })();
+"#;
 
 fn main() {
     // Set config path for Tauri app
-    set_tauri_config_path();
+    let paths = set_tauri_config_path();
+    
+    // Only one instance may run the server; later launches activate it and exit
+    let instance = match instance::acquire(&paths.config_dir) {
+        Some(lock) => lock,
+        None => return,
+    };
     
-    // Start the Node.js server
-    let server = start_server();
+    let settings = Settings::load(&paths.config_dir);
     
-    let server_process = ServerProcess(Mutex::new(server));
+    // The Node.js server, streaming its output to the logs directory. Its port,
+    // the vault and node are all sorted out from setup, behind the splash page.
+    let server_process = ServerProcess::new(ServerLogs::new(paths.logs_dir()));
+    server_process.set_node_path(settings.get().node_path);
     
-    // Wait a moment for the server to start
-    std::thread::sleep(std::time::Duration::from_secs(2));
+    let handler = tauri::generate_handler![
+        external::open_external,
+        server::server_exits,
+        logs::server_logs,
+        settings::get_shell_settings,
+        settings::update_shell_settings,
+        startup::server_state,
+        startup::retry_server,
+        startup::open_logs,
+        paths::get_app_paths,
+        import::find_import_sources,
+        import::preview_import,
+        import::run_import,
+        sync::refresh_service,
+        hotkeys::set_toggle_shortcut,
+        hotkeys::set_quick_add_shortcut,
+        quickadd::parse_quick_task,
+        quickadd::add_quick_task,
+        hotkeys::set_palette_shortcut,
+        palette::search_palette,
+        palette::run_palette_action
+    ];
     
     tauri::Builder::default()
-        .invoke_handler(tauri::generate_handler![open_external])
+        .invoke_handler(move |invoke| {
+            // Pages from the server only get the commands they use
+            let command = invoke.message.command().to_string();
+            if !server::page_may_call(invoke.message.window_ref(), &command) {
+                invoke
+                    .resolver
+                    .reject(format!("{} is not available to dashboard pages", command));
+                return;
+            }
+            handler(invoke)
+        })
         .manage(server_process)
-        .setup(|app| {
-            let window = app.get_window("main").unwrap();
-            
-            // Navigate to the local server URL
-            window.eval(&format!("window.location.replace('http://localhost:1337')")).ok();
+        .manage(settings)
+        .manage(instance)
+        .manage(Feed::new())
+        .manage(SyncState::new())
+        .manage(Hotkeys::new())
+        .manage(Palette::new())
+        .manage(WindowState::load(&paths.config_dir))
+        .manage(paths)
+        .system_tray(tray::build())
+        .on_system_tray_event(tray::handle_event)
+        .on_window_event(|event| {
+            // Remember where the main window is, including just before it closes
+            geometry::track(&event);
             
-            // Wait for server to be ready, then show window
-            std::thread::spawn(move || {
-                let mut retries = 0;
-                loop {
-                    std::thread::sleep(std::time::Duration::from_secs(1));
-                    
-                    // Try to connect to the server
-                    if reqwest::blocking::get("http://localhost:1337/health").is_ok() {
-                        // Inject link handler after server is ready
-                        let js = r#"
-                            (function() {
-                                if (window.__TAURI_LINK_HANDLER__) return;
-                                window.__TAURI_LINK_HANDLER__ = true;
-                                
-                                document.addEventListener('click', function(e) {
-                                    const link = e.target.closest('a[href]');
-                                    if (!link) return;
-                                    
-                                    const href = link.getAttribute('href');
-                                    if (!href || href.startsWith('#')) return;
-                                    
-                                    try {
-                                        const url = new URL(href, window.location.href);
-                                        if (url.hostname !== 'localhost' && url.hostname !== '127.0.0.1') {
-                                            e.preventDefault();
-                                            e.stopPropagation();
-                                            // Call Tauri command
-                                            window.__TAURI__.invoke('open_external', { url: url.href });
-                                            return false;
-                                        }
-                                    } catch (e) {}
-                                }, true);
-                                
-                                console.log('Tauri link handler installed');
-                            })();
-                        "#;
-                        window.eval(js).ok();
-                        
-                        window.show().unwrap();
-                        window.set_focus().unwrap();
-                        break;
-                    }
-                    
-                    retries += 1;
-                    if retries > 30 {
-                        window.show().unwrap();
-                        break;
+            // Closing the main window hides it to the tray, or quits if the user opted out
+            if let tauri::WindowEvent::CloseRequested { api, .. } = event.event() {
+                let window = event.window();
+                if window.label() == "main" {
+                    api.prevent_close();
+                    if window.state::<Settings>().get().close_to_tray {
+                        tray::hide_window(&window.app_handle());
+                    } else {
+                        // The hidden quick-add and palette windows would otherwise keep it running
+                        tray::quit(&window.app_handle());
                     }
                 }
-            });
+                // The quick-add and palette windows are reused, so closing one (e.g. Cmd+W) only hides it
+                if window.label() == quickadd::WINDOW || window.label() == palette::WINDOW {
+                    api.prevent_close();
+                    let _ = window.hide();
+                }
+            }
+        })
+        .on_page_load(|window, payload| {
+            // Route external links through open_external on every dashboard page
+            if payload.url().starts_with(&server::page_url("/")) {
+                window.eval(LINK_HANDLER_JS).ok();
+            }
+            startup::remember_splash(payload.url());
+        })
+        .setup(|app| {
+            // The dashboard and settings pages call into the shell
+            server::allow_page_ipc(&app.handle());
+            
+            // Restart the server if it crashes
+            server::supervise(app.handle());
+            
+            // Bring this window forward when Mission Control is launched again
+            app.state::<InstanceLock>().listen(&app.handle());
+            
+            // Show the splash page right away; it switches to the dashboard once
+            // the server is ready, or explains what went wrong
+            let handle = app.handle();
+            startup::follow(&handle);
+            startup::launch(&handle);
+            geometry::restore(&handle);
+            tray::show_window(&handle);
+            
+            // Watch dashboard data for things worth a desktop notification
+            feed::poll(&handle);
+            notifications::watch(&handle);
+            reminders::watch(&handle);
+            badge::watch(&handle);
+            tray::watch(&handle);
+            palette::watch(&handle);
+            
+            // Global keyboard shortcuts from desktop.json
+            hotkeys::register(&handle);
             
             Ok(())
         })
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+        .build(tauri::generate_context!())
+        .expect("error while building tauri application")
+        .run(|app, event| {
+            if let tauri::RunEvent::Exit = event {
+                app.state::<WindowState>().flush();
+                // Give server.js a chance to stop the scheduler and close the database
+                app.state::<ServerProcess>().shutdown();
+                app.state::<InstanceLock>().release();
+            }
+        });
 }
 
-fn set_tauri_config_path() {
-    let config_dir = if cfg!(target_os = "macos") {
-        env::var("HOME")
-            .map(|home| PathBuf::from(home).join(".mission-control"))
-            .unwrap_or_else(|_| PathBuf::from(".mission-control"))
-    } else {
-        PathBuf::from(".mission-control")
-    };
+fn set_tauri_config_path() -> AppPaths {
+    let paths = AppPaths::resolve();
+    paths.create();
+    paths.migrate_legacy();
     
-    if !config_dir.exists() {
-        let _ = std::fs::create_dir_all(&config_dir);
-    }
-    
-    let config_path = config_dir.join("config.json");
-    env::set_var("MISSION_CONTROL_CONFIG", config_path.to_str().unwrap());
+    // The server inherits these along with the database, log and cache paths
+    paths.export();
     env::set_var("TAURI_PLATFORM", "true");
     
-    println!("Tauri config path set to: {:?}", config_path);
-}
-
-fn start_server() -> Option<std::process::Child> {
-    use std::env;
-    
-    let exe_path = env::current_exe().ok()?;
-    let exe_dir = exe_path.parent()?;
-    
-    println!("Executable path: {:?}", exe_path);
-    println!("Executable dir: {:?}", exe_dir);
-    
-    let possible_roots = [
-        env::current_dir().ok()?,
-        exe_dir.join("../Resources"),
-        exe_dir.to_path_buf(),
-        exe_dir.join("../../.."),
-    ];
-    
-    for root in &possible_roots {
-        let server_script = root.join("src/server.js");
-        if server_script.exists() {
-            println!("Found server at: {:?}", server_script);
-            
-            let working_dir = if root.join("node_modules").exists() {
-                root.clone()
-            } else {
-                server_script.parent()?.parent()?.to_path_buf()
-            };
-            
-            println!("Working directory: {:?}", working_dir);
-            
-            let child = Command::new("node")
-                .arg(&server_script)
-                .current_dir(&working_dir)
-                .env("NODE_ENV", "production")
-                .stdout(Stdio::piped())
-                .stderr(Stdio::piped())
-                .spawn()
-                .map_err(|e| {
-                    println!("Failed to spawn node: {}", e);
-                    e
-                })
-                .ok()?;
-            
-            println!("Server started with PID: {:?}", child.id());
-            return Some(child);
-        }
-    }
-    
-    println!("Could not find src/server.js in any of: {:?}", possible_roots);
-    None
+    println!("Tauri config path set to: {:?}", paths.config_file());
+    println!("Tauri data directory: {:?}", paths.data_dir);
+    paths
 }
@@ -0,0 +1,252 @@
+use crate::paths::home_dir;
+use std::collections::HashSet;
+use std::env;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+// Oldest Node.js that better-sqlite3 9.x ships prebuilt binaries for and that
+// TAURI.md lists as a prerequisite
+const MIN_NODE_MAJOR: u32 = 18;
+
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Version(u32, u32, u32);
+
+impl Version {
+    // Parses `node --version` output such as "v20.11.1"
+    fn parse(text: &str) -> Option<Version> {
+        let mut parts = text.trim().trim_start_matches('v').split('.');
+        let major = parts.next()?.parse().ok()?;
+        let minor = parts.next().and_then(|p| p.parse().ok()).unwrap_or(0);
+        let patch = parts.next().and_then(|p| p.parse().ok()).unwrap_or(0);
+        Some(Version(major, minor, patch))
+    }
+}
+
+impl fmt::Display for Version {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "v{}.{}.{}", self.0, self.1, self.2)
+    }
+}
+
+#[derive(Clone)]
+pub struct NodeRuntime {
+    pub path: PathBuf,
+    pub version: Version,
+    pub source: &'static str,
+}
+
+// Loads the installed better-sqlite3 the way the server does. Its native addon
+// only works with the Node.js ABI it was built for, so a newer node that
+// passes the version check can still crash-loop the server.
+const ADDON_CHECK: &str = "new (require('better-sqlite3'))(':memory:').close()";
+
+// Find a usable node binary for the server in `root`, in order: the explicit
+// setting, a bundled sidecar, the version managers' defaults, common install
+// locations and PATH, then any other version manager install
+pub fn resolve(explicit: Option<&Path>, root: &Path) -> Result<NodeRuntime, String> {
+    if let Some(path) = explicit {
+        // Don't second-guess an explicit choice by falling back to something else
+        return check("settings", path.to_path_buf(), root)
+            .map_err(|reason| format!("nodePath in desktop.json is not usable: {}", reason));
+    }
+
+    let mut rejected = Vec::new();
+    for (source, path) in candidates() {
+        match check(source, path, root) {
+            Ok(runtime) => {
+                println!(
+                    "Using node {} from {} ({:?})",
+                    runtime.version, runtime.source, runtime.path
+                );
+                return Ok(runtime);
+            }
+            Err(reason) => rejected.push(reason),
+        }
+    }
+
+    let mut message = format!(
+        "No usable Node.js {} or newer found. Install it, or set nodePath in desktop.json.",
+        MIN_NODE_MAJOR
+    );
+    if !rejected.is_empty() {
+        message.push_str(" Skipped: ");
+        message.push_str(&rejected.join("; "));
+    }
+    Err(message)
+}
+
+// Check a runtime found earlier again, e.g. before a restart, without going
+// through every candidate
+pub fn recheck(runtime: NodeRuntime, root: &Path) -> Result<NodeRuntime, String> {
+    check(runtime.source, runtime.path, root)
+}
+
+fn check(source: &'static str, path: PathBuf, root: &Path) -> Result<NodeRuntime, String> {
+    let output = Command::new(&path)
+        .arg("--version")
+        .output()
+        .map_err(|e| format!("{} ({})", path.display(), e))?;
+
+    let version = Version::parse(&String::from_utf8_lossy(&output.stdout))
+        .ok_or_else(|| format!("{} (unrecognised version)", path.display()))?;
+
+    if version.0 < MIN_NODE_MAJOR {
+        return Err(format!("{} ({} is too old)", path.display(), version));
+    }
+
+    let output = Command::new(&path)
+        .args(["-e", ADDON_CHECK])
+        .current_dir(root)
+        .output()
+        .map_err(|e| format!("{} ({})", path.display(), e))?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(if stderr.contains("NODE_MODULE_VERSION") {
+            format!(
+                "{} ({} doesn't match the Node.js better-sqlite3 was built for; run npm rebuild with it)",
+                path.display(),
+                version
+            )
+        } else {
+            format!(
+                "{} ({} could not load better-sqlite3: {})",
+                path.display(),
+                version,
+                stderr
+                    .lines()
+                    .find(|line| line.contains("Error"))
+                    .unwrap_or("no details")
+            )
+        });
+    }
+
+    Ok(NodeRuntime {
+        path,
+        version,
+        source,
+    })
+}
+
+fn candidates() -> Vec<(&'static str, PathBuf)> {
+    let exe = format!("node{}", env::consts::EXE_SUFFIX);
+    let mut found = Vec::new();
+
+    // Tauri places externalBin sidecars next to the app executable
+    if let Some(dir) = env::current_exe()
+        .ok()
+        .and_then(|p| p.parent().map(Path::to_path_buf))
+    {
+        found.push(("bundled", dir.join(&exe)));
+    }
+
+    // What the user picked as their default, before anything newer they happen
+    // to have installed. Volta's shim already runs its default.
+    let home = home_dir();
+    let fnm_dirs: Vec<PathBuf> = home
+        .iter()
+        .flat_map(|home| {
+            [
+                home.join(".local/share/fnm"),
+                home.join("Library/Application Support/fnm"),
+            ]
+        })
+        .collect();
+    if let Some(home) = &home {
+        found.push(("volta", home.join(".volta/bin").join(&exe)));
+        if let Some(dir) = nvm_default(&home.join(".nvm")) {
+            found.push(("nvm default", dir.join("bin").join(&exe)));
+        }
+        for fnm in &fnm_dirs {
+            found.push(("fnm default", fnm.join("aliases/default/bin").join(&exe)));
+        }
+        if let Some(version) = asdf_global(home) {
+            let dir = home.join(".asdf/installs/nodejs").join(version);
+            found.push(("asdf global", dir.join("bin").join(&exe)));
+        }
+    }
+
+    // Homebrew and the official installers, which a launcher's PATH often lacks
+    for dir in ["/opt/homebrew/bin", "/usr/local/bin"] {
+        found.push(("system", Path::new(dir).join(&exe)));
+    }
+
+    if let Some(path) = env::var_os("PATH") {
+        for dir in env::split_paths(&path) {
+            found.push(("PATH", dir.join(&exe)));
+        }
+    }
+
+    // Anything else a version manager installed, newest first
+    if let Some(home) = &home {
+        for dir in newest_first(home.join(".nvm/versions/node")) {
+            found.push(("nvm", dir.join("bin").join(&exe)));
+        }
+        for fnm in &fnm_dirs {
+            for dir in newest_first(fnm.join("node-versions")) {
+                found.push(("fnm", dir.join("installation/bin").join(&exe)));
+            }
+        }
+        for dir in newest_first(home.join(".asdf/installs/nodejs")) {
+            found.push(("asdf", dir.join("bin").join(&exe)));
+        }
+    }
+
+    let mut seen = HashSet::new();
+    found.retain(|(_, path)| path.is_file() && seen.insert(path.clone()));
+    found
+}
+
+// Version directories (e.g. v20.11.1, 18.19.0) sorted newest first
+fn newest_first(dir: PathBuf) -> Vec<PathBuf> {
+    let mut versions: Vec<(Version, PathBuf)> = fs::read_dir(dir)
+        .map(|entries| {
+            entries
+                .filter_map(|entry| entry.ok())
+                .filter_map(|entry| {
+                    let name = entry.file_name();
+                    Version::parse(&name.to_string_lossy()).map(|v| (v, entry.path()))
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    versions.sort_by_key(|(version, _)| std::cmp::Reverse(*version));
+    versions.into_iter().map(|(_, path)| path).collect()
+}
+
+// The install nvm's default alias points at. Aliases hold a version prefix
+// ("20", "v20.11.1") or name another alias ("lts/iron"), so follow them.
+fn nvm_default(nvm: &Path) -> Option<PathBuf> {
+    let mut spec = fs::read_to_string(nvm.join("alias/default")).ok()?;
+    for _ in 0..5 {
+        match fs::read_to_string(nvm.join("alias").join(spec.trim())) {
+            Ok(next) => spec = next,
+            Err(_) => break,
+        }
+    }
+
+    let installed = newest_first(nvm.join("versions/node"));
+    let spec = spec.trim().trim_start_matches('v');
+    if matches!(spec, "node" | "stable") {
+        return installed.into_iter().next();
+    }
+    installed.into_iter().find(|dir| {
+        let name = dir.file_name().unwrap_or_default().to_string_lossy();
+        let version = name.trim_start_matches('v');
+        version == spec || version.starts_with(&format!("{}.", spec))
+    })
+}
+
+// The nodejs version in ~/.tool-versions, which asdf uses outside projects
+fn asdf_global(home: &Path) -> Option<String> {
+    let versions = fs::read_to_string(home.join(".tool-versions")).ok()?;
+    versions.lines().find_map(|line| {
+        let mut words = line.split_whitespace();
+        if words.next() == Some("nodejs") {
+            words.next().map(str::to_string)
+        } else {
+            None
+        }
+    })
+}
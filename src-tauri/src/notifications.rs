@@ -0,0 +1,266 @@
+use crate::external;
+use crate::feed::{DashboardData, Feed};
+use crate::notify;
+use crate::paths::AppPaths;
+use crate::settings::Settings;
+use crate::tray;
+use std::collections::{BTreeMap, HashSet, VecDeque};
+use std::fs;
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Manager};
+
+// At most this many notifications per window; the rest are folded into one
+const MAX_PER_WINDOW: usize = 4;
+const RATE_WINDOW: Duration = Duration::from_secs(60);
+
+// Titles listed in a grouped notification before "and N more"
+const BODY_LINES: usize = 3;
+
+// New items for one repo or story, announced as a single notification
+struct Group {
+    title: String,
+    lines: Vec<String>,
+    url: String,
+}
+
+// Remembers what has been announced, across restarts, so each GitHub or
+// Shortcut notification is only announced once
+struct Announcer {
+    path: PathBuf,
+    announced: Option<HashSet<String>>,
+    shown: VecDeque<Instant>,
+}
+
+// Announce new GitHub and Shortcut notifications as they reach the dashboard
+pub fn watch(app: &AppHandle) {
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let mut announcer = Announcer::load(app.state::<AppPaths>().data_dir.join("notified.json"));
+        let mut updates = app.state::<Feed>().subscribe();
+        while updates.changed().await.is_ok() {
+            let data = updates.borrow_and_update().clone();
+            if let Some(data) = data {
+                announcer.announce(&app, &data);
+            }
+        }
+    });
+}
+
+impl Announcer {
+    fn load(path: PathBuf) -> Self {
+        let announced = fs::read_to_string(&path)
+            .ok()
+            .and_then(|data| serde_json::from_str(&data).ok());
+        Announcer {
+            path,
+            announced,
+            shown: VecDeque::new(),
+        }
+    }
+
+    fn save(&self) {
+        if let Some(announced) = &self.announced {
+            if let Ok(data) = serde_json::to_string(announced) {
+                let _ = fs::write(&self.path, data);
+            }
+        }
+    }
+
+    fn announce(&mut self, app: &AppHandle, data: &DashboardData) {
+        let current = unread(data);
+
+        // First run: everything already unread counts as announced
+        let Some(announced) = &mut self.announced else {
+            self.announced = Some(current.keys().cloned().collect());
+            self.save();
+            return;
+        };
+        announced.retain(|key| current.contains_key(key));
+
+        let (fresh_keys, fresh): (Vec<&String>, Vec<&Item>) = current
+            .iter()
+            .filter(|(key, _)| !announced.contains(*key))
+            .unzip();
+        if fresh.is_empty() {
+            self.save();
+            return;
+        }
+
+        // Nothing to do while the dashboard itself is in front of the user
+        if app.state::<Settings>().get().desktop_notifications && !tray::dashboard_in_front(app) {
+            let now = Instant::now();
+            while self
+                .shown
+                .front()
+                .is_some_and(|at| now.duration_since(*at) > RATE_WINDOW)
+            {
+                self.shown.pop_front();
+            }
+            let budget = MAX_PER_WINDOW.saturating_sub(self.shown.len());
+            if budget == 0 {
+                // Leave them unannounced and try again on the next update
+                return;
+            }
+
+            let mut groups = group(data, &fresh);
+            let overflow = if groups.len() > budget {
+                groups.split_off(budget - 1)
+            } else {
+                Vec::new()
+            };
+            for group in groups {
+                self.shown.push_back(now);
+                let app = app.clone();
+                notify::show(group.title, group.lines.join("\n"), Vec::new(), move |_| {
+                    let _ = external::open(&app, &group.url);
+                });
+            }
+            if !overflow.is_empty() {
+                self.shown.push_back(now);
+                let app = app.clone();
+                notify::show(
+                    "Mission Control".to_string(),
+                    format!("New notifications in {} more places", overflow.len()),
+                    Vec::new(),
+                    move |_| tray::show_window(&app),
+                );
+            }
+        }
+
+        let announced = self.announced.get_or_insert_with(HashSet::new);
+        announced.extend(fresh_keys.into_iter().cloned());
+        self.save();
+    }
+}
+
+enum Item {
+    Github {
+        repo: String,
+        title: String,
+        url: String,
+    },
+    Shortcut {
+        story_id: Option<i64>,
+        line: String,
+    },
+}
+
+// Unread notifications keyed so that a GitHub thread with new activity counts
+// as new again
+fn unread(data: &DashboardData) -> BTreeMap<String, Item> {
+    let mut items = BTreeMap::new();
+
+    for n in &data.github_notifications {
+        let owner = n.repository_owner.as_deref().unwrap_or_default();
+        let name = n.repository_name.as_deref().unwrap_or_default();
+        let repo = format!("{}/{}", owner, name);
+        let title = match (n.subject_title.as_deref(), n.reason.as_deref()) {
+            (Some(title), Some("review_requested")) => format!("Review requested: {}", title),
+            (Some(title), _) => title.to_string(),
+            (None, _) => "New activity".to_string(),
+        };
+        let url = n
+            .subject_url
+            .as_deref()
+            .map(web_url)
+            .unwrap_or_else(|| format!("https://github.com/{}", repo));
+
+        let key = format!(
+            "github:{}:{}",
+            n.notification_id,
+            n.updated_at.as_deref().unwrap_or_default()
+        );
+        items.insert(key, Item::Github { repo, title, url });
+    }
+
+    for n in &data.shortcut_notifications {
+        let message = n.message.as_deref().unwrap_or("New activity");
+        let line = match n.actor_name.as_deref() {
+            Some(actor) => format!("{}: {}", actor, message),
+            None => message.to_string(),
+        };
+        let key = format!("shortcut:{}", n.notification_id);
+        items.insert(
+            key,
+            Item::Shortcut {
+                story_id: n.story_id,
+                line,
+            },
+        );
+    }
+
+    items
+}
+
+// One group per repo or story, in the order they first appear
+fn group(data: &DashboardData, items: &[&Item]) -> Vec<Group> {
+    let mut groups: Vec<(String, Group)> = Vec::new();
+
+    for item in items {
+        let (key, title, line, url) = match item {
+            Item::Github { repo, title, url } => (
+                format!("github:{}", repo),
+                repo.clone(),
+                title.clone(),
+                url.clone(),
+            ),
+            Item::Shortcut { story_id, line } => {
+                let name = story_id.and_then(|id| {
+                    data.shortcut_stories
+                        .iter()
+                        .find(|story| story.story_id == id)
+                        .map(|story| story.name.clone())
+                });
+                let title = match (name, story_id) {
+                    (Some(name), _) => name,
+                    (None, Some(id)) => format!("Shortcut story #{}", id),
+                    (None, None) => "Shortcut".to_string(),
+                };
+                let url = match story_id {
+                    Some(id) => format!("https://app.shortcut.com/story/{}", id),
+                    None => "https://app.shortcut.com".to_string(),
+                };
+                (format!("shortcut:{:?}", story_id), title, line.clone(), url)
+            }
+        };
+
+        match groups.iter_mut().find(|(k, _)| *k == key) {
+            Some((_, group)) => {
+                group.lines.push(line);
+                // Several threads in one repo: open the repo's notification list
+                if let Item::Github { repo, .. } = item {
+                    group.url = format!("https://github.com/notifications?query=repo%3A{}", repo);
+                }
+            }
+            None => groups.push((
+                key,
+                Group {
+                    title,
+                    lines: vec![line],
+                    url,
+                },
+            )),
+        }
+    }
+
+    groups
+        .into_iter()
+        .map(|(_, mut group)| {
+            if group.lines.len() > BODY_LINES {
+                let more = group.lines.len() - BODY_LINES;
+                group.lines.truncate(BODY_LINES);
+                group.lines.push(format!("and {} more", more));
+            }
+            group
+        })
+        .collect()
+}
+
+// GitHub's notification API links to api.github.com; the dashboard rewrites
+// them the same way
+pub fn web_url(api_url: &str) -> String {
+    api_url
+        .replace("api.github.com/repos/", "github.com/")
+        .replace("/pulls/", "/pull/")
+}
@@ -0,0 +1,233 @@
+// Native desktop notifications with click and button callbacks. Tauri's own
+// notification API can't report clicks, so this talks to the platform crates.
+
+#[cfg(target_os = "macos")]
+use std::sync::atomic::{AtomicUsize, Ordering};
+#[cfg(all(unix, not(target_os = "macos")))]
+use std::time::Duration;
+
+// Passed to the callback when the notification body itself is clicked
+pub const CLICKED: &str = "default";
+
+// A button on the notification, reported back by id
+pub struct Action {
+    pub id: &'static str,
+    pub label: &'static str,
+}
+
+// The bundle identifier from tauri.conf.json, which the installers register
+// with the OS as the app's notification sender
+#[cfg(any(target_os = "macos", windows))]
+const APP_ID: &str = "com.finlay.missioncontrol";
+
+// GNOME and KDE keep notifications in a history and never report them closed,
+// so a click is only waited for this long
+#[cfg(all(unix, not(target_os = "macos")))]
+const RESPONSE_WAIT: Duration = Duration::from_secs(15 * 60);
+
+// Notification Center keeps notifications until they're dismissed, and a wait
+// there can't be cut short, so only this many are waited on at once
+#[cfg(target_os = "macos")]
+const MAX_WAITING: usize = 4;
+
+#[cfg(target_os = "macos")]
+static WAITING: AtomicUsize = AtomicUsize::new(0);
+
+// Show a notification and call `on_action` with CLICKED or the id of the
+// button the user pressed. macOS shows at most two buttons.
+pub fn show<F>(title: String, body: String, actions: Vec<Action>, on_action: F)
+where
+    F: FnOnce(&str) + Send + 'static,
+{
+    // Linux waits on D-Bus asynchronously, with a time limit
+    #[cfg(all(unix, not(target_os = "macos")))]
+    tauri::async_runtime::spawn(async move {
+        if let Err(e) = deliver(&title, &body, &actions, on_action).await {
+            println!("Could not show notification \"{}\": {}", title, e);
+        }
+    });
+
+    // Elsewhere waiting blocks, so each notification gets its own thread
+    #[cfg(any(windows, target_os = "macos"))]
+    std::thread::spawn(move || {
+        if let Err(e) = deliver(&title, &body, &actions, on_action) {
+            println!("Could not show notification \"{}\": {}", title, e);
+        }
+    });
+}
+
+// Show a notification without waiting for the user, for when the app is
+// about to exit and no one would be left to handle a click
+pub fn alert(title: &str, body: &str) {
+    if let Err(e) = post(title, body) {
+        println!("Could not show notification \"{}\": {}", title, e);
+    }
+}
+
+#[cfg(not(target_os = "macos"))]
+fn post(title: &str, body: &str) -> Result<(), String> {
+    new_notification(title, body)
+        .show()
+        .map(|_| ())
+        .map_err(|e| e.to_string())
+}
+
+#[cfg(target_os = "macos")]
+fn post(title: &str, body: &str) -> Result<(), String> {
+    let _ = mac_notification_sys::set_application(APP_ID);
+    mac_notification_sys::Notification::new()
+        .title(title)
+        .message(body)
+        .send()
+        .map(|_| ())
+        .map_err(|e| e.to_string())
+}
+
+// notify-rust reports body clicks and buttons on Linux and Windows
+#[cfg(all(unix, not(target_os = "macos")))]
+async fn deliver<F: FnOnce(&str)>(
+    title: &str,
+    body: &str,
+    actions: &[Action],
+    on_action: F,
+) -> Result<(), String> {
+    use notify_rust::NotificationResponse;
+
+    let mut notification = new_notification(title, body);
+    for action in actions {
+        notification.action(action.id, action.label);
+    }
+    let handle = notification.show_async().await.map_err(|e| e.to_string())?;
+
+    let response = handle.wait_for_action_async(|response: &NotificationResponse| match response {
+        NotificationResponse::Default => on_action(CLICKED),
+        NotificationResponse::Action(action) => on_action(action),
+        _ => {}
+    });
+    // Stop listening after a while; the notification itself stays put
+    let _ = tokio::time::timeout(RESPONSE_WAIT, response).await;
+    Ok(())
+}
+
+// Windows reports a toast dismissed once it times out into Action Center, so
+// this wait ends by itself
+#[cfg(windows)]
+fn deliver<F: FnOnce(&str)>(
+    title: &str,
+    body: &str,
+    actions: &[Action],
+    on_action: F,
+) -> Result<(), String> {
+    use notify_rust::NotificationResponse;
+
+    let mut notification = new_notification(title, body);
+    for action in actions {
+        notification.action(action.id, action.label);
+    }
+    let handle = notification.show().map_err(|e| e.to_string())?;
+
+    handle
+        .wait_for_response(|response: &NotificationResponse| match response {
+            NotificationResponse::Default => on_action(CLICKED),
+            NotificationResponse::Action(action) => on_action(action),
+            _ => {}
+        })
+        .map_err(|e| e.to_string())
+}
+
+#[cfg(not(target_os = "macos"))]
+fn new_notification(title: &str, body: &str) -> notify_rust::Notification {
+    let mut notification = notify_rust::Notification::new();
+    notification
+        .appname("Mission Control")
+        .summary(title)
+        .body(body);
+    // Lets the notification server map a click on the body to "default"
+    #[cfg(unix)]
+    notification.action(CLICKED, "Open");
+    // Toasts only come back to the installed app's ID; dev builds run from
+    // target/ aren't registered, so they keep notify-rust's default
+    #[cfg(windows)]
+    {
+        if !running_from_target() {
+            notification.app_id(APP_ID);
+        }
+    }
+    notification
+}
+
+#[cfg(windows)]
+fn running_from_target() -> bool {
+    std::env::current_exe()
+        .ok()
+        .and_then(|exe| exe.parent().map(std::path::Path::to_path_buf))
+        .map(|dir| dir.ends_with("target/debug") || dir.ends_with("target/release"))
+        .unwrap_or(false)
+}
+
+#[cfg(target_os = "macos")]
+fn deliver<F: FnOnce(&str)>(
+    title: &str,
+    body: &str,
+    actions: &[Action],
+    on_action: F,
+) -> Result<(), String> {
+    use mac_notification_sys::{MainButton, Notification, NotificationResponse};
+
+    // Fails harmlessly once the bundle has already been registered
+    let _ = mac_notification_sys::set_application(APP_ID);
+
+    // Past the limit the notification still shows, but its click is ignored
+    let waiting = Waiting::claim();
+    let mut notification = Notification::new();
+    notification
+        .title(title)
+        .message(body)
+        .wait_for_click(waiting.is_some());
+    if let Some(action) = actions.first() {
+        notification.main_button(MainButton::SingleAction(action.label));
+    }
+    if let Some(action) = actions.get(1) {
+        notification.close_button(action.label);
+    }
+
+    let id_for = |label: &str| {
+        actions
+            .iter()
+            .find(|action| action.label == label)
+            .map(|action| action.id)
+    };
+    match notification.send().map_err(|e| e.to_string())? {
+        NotificationResponse::Click => on_action(CLICKED),
+        NotificationResponse::ActionButton(label) | NotificationResponse::CloseButton(label) => {
+            if let Some(id) = id_for(&label) {
+                on_action(id);
+            }
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+// One of the MAX_WAITING slots, given back when dropped
+#[cfg(target_os = "macos")]
+struct Waiting;
+
+#[cfg(target_os = "macos")]
+impl Waiting {
+    fn claim() -> Option<Waiting> {
+        WAITING
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |count| {
+                (count < MAX_WAITING).then_some(count + 1)
+            })
+            .ok()
+            .map(|_| Waiting)
+    }
+}
+
+#[cfg(target_os = "macos")]
+impl Drop for Waiting {
+    fn drop(&mut self) {
+        WAITING.fetch_sub(1, Ordering::SeqCst);
+    }
+}
@@ -0,0 +1,413 @@
+use crate::error::ShellError;
+use crate::external;
+use crate::feed::{DashboardData, Feed};
+use crate::notifications;
+use crate::reminders;
+use crate::server;
+use crate::sync;
+use crate::tray;
+use chrono::{DateTime, Local};
+use serde::Serialize;
+use std::sync::Mutex;
+use tauri::{AppHandle, ClipboardManager, Manager};
+
+pub const WINDOW: &str = "palette";
+
+// Results returned per search; the window only has room for a screenful
+const MAX_RESULTS: usize = 50;
+
+// One searchable thing from the dashboard and what can be done with it
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Entry {
+    pub id: String,
+    // "pr", "story", "task", "event" or "notification"
+    pub kind: &'static str,
+    pub title: String,
+    pub subtitle: String,
+    // Any of "open", "copyPrompt", "complete" and "markRead"; the first is
+    // the default
+    pub actions: Vec<&'static str>,
+    #[serde(skip)]
+    url: Option<String>,
+    #[serde(skip)]
+    target: Target,
+}
+
+#[derive(Clone)]
+enum Target {
+    Pr { url: String },
+    Story { id: i64, name: String },
+    Task { id: String },
+    Event,
+    GithubNotification { id: String },
+    ShortcutNotification { id: String },
+}
+
+// Search index built from the latest dashboard data, so the palette answers
+// instantly even while the dashboard is hidden
+pub struct Palette {
+    entries: Mutex<Vec<Entry>>,
+}
+
+impl Palette {
+    pub fn new() -> Self {
+        Palette {
+            entries: Mutex::new(Vec::new()),
+        }
+    }
+
+    // Best matches first; everything, in index order, for an empty query
+    pub fn search(&self, query: &str) -> Vec<Entry> {
+        let entries = self.entries.lock().unwrap();
+        let mut scored: Vec<(i64, &Entry)> = entries
+            .iter()
+            .filter_map(|entry| {
+                let text = format!("{} {} {}", entry.title, entry.subtitle, entry.kind);
+                score(query, &text).map(|score| (score, entry))
+            })
+            .collect();
+        // Stable, so equal scores keep the index order
+        scored.sort_by_key(|(score, _)| std::cmp::Reverse(*score));
+        scored
+            .into_iter()
+            .take(MAX_RESULTS)
+            .map(|(_, entry)| entry.clone())
+            .collect()
+    }
+
+    fn get(&self, id: &str) -> Option<Entry> {
+        self.entries
+            .lock()
+            .unwrap()
+            .iter()
+            .find(|entry| entry.id == id)
+            .cloned()
+    }
+
+    // Drop an entry that was just completed or read, ahead of the next fetch
+    fn remove(&self, id: &str) {
+        self.entries.lock().unwrap().retain(|entry| entry.id != id);
+    }
+}
+
+// Rebuild the index whenever fresh dashboard data arrives
+pub fn watch(app: &AppHandle) {
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let mut updates = app.state::<Feed>().subscribe();
+        while updates.changed().await.is_ok() {
+            let data = updates.borrow_and_update().clone();
+            if let Some(data) = data {
+                *app.state::<Palette>().entries.lock().unwrap() = index(&data);
+            }
+        }
+    });
+}
+
+fn index(data: &DashboardData) -> Vec<Entry> {
+    let mut entries = Vec::new();
+
+    for pr in &data.github_prs {
+        let owner = pr.repo_owner.as_deref().unwrap_or_default();
+        let repo = pr.repo_name.as_deref().unwrap_or_default();
+        let url = pr
+            .html_url
+            .clone()
+            .unwrap_or_else(|| format!("https://github.com/{}/{}/pull/{}", owner, repo, pr.number));
+        let review = if pr.review_requested == Some(1) {
+            " · review requested"
+        } else {
+            ""
+        };
+        entries.push(Entry {
+            id: format!("pr:{}/{}#{}", owner, repo, pr.number),
+            kind: "pr",
+            title: pr.title.clone(),
+            subtitle: format!("{}/{}#{}{}", owner, repo, pr.number, review),
+            actions: vec!["open", "copyPrompt"],
+            url: Some(url.clone()),
+            target: Target::Pr { url },
+        });
+    }
+
+    for story in &data.shortcut_stories {
+        entries.push(Entry {
+            id: format!("story:{}", story.story_id),
+            kind: "story",
+            title: story.name.clone(),
+            subtitle: format!("Shortcut #{}", story.story_id),
+            actions: vec!["open", "copyPrompt"],
+            url: Some(story_url(story.story_id)),
+            target: Target::Story {
+                id: story.story_id,
+                name: story.name.clone(),
+            },
+        });
+    }
+
+    for task in &data.todoist_tasks {
+        let due = task
+            .due_date
+            .as_deref()
+            .map(|due| format!(" · due {}", due))
+            .unwrap_or_default();
+        let url = task
+            .url
+            .clone()
+            .unwrap_or_else(|| format!("https://app.todoist.com/app/task/{}", task.task_id));
+        entries.push(Entry {
+            id: format!("task:{}", task.task_id),
+            kind: "task",
+            title: task.content.clone(),
+            subtitle: format!("Todoist{}", due),
+            actions: vec!["open", "complete"],
+            url: Some(url),
+            target: Target::Task {
+                id: task.task_id.clone(),
+            },
+        });
+    }
+
+    for event in &data.calendar {
+        let time = DateTime::parse_from_rfc3339(&event.start_time)
+            .map(|start| start.with_timezone(&Local).format("%H:%M").to_string())
+            .unwrap_or_default();
+        let calendar = event.calendar_type.as_deref().unwrap_or("calendar");
+        let link = reminders::meeting_link(event.location.as_deref().unwrap_or_default())
+            .or_else(|| reminders::meeting_link(event.description.as_deref().unwrap_or_default()));
+        entries.push(Entry {
+            id: format!("event:{}", event.uid),
+            kind: "event",
+            title: event.summary.clone(),
+            subtitle: format!("{} · {}", time, calendar),
+            actions: vec!["open"],
+            url: link,
+            target: Target::Event,
+        });
+    }
+
+    for n in &data.github_notifications {
+        let owner = n.repository_owner.as_deref().unwrap_or_default();
+        let repo = n.repository_name.as_deref().unwrap_or_default();
+        let reason = n
+            .reason
+            .as_deref()
+            .map(|reason| format!(" · {}", reason.replace('_', " ")))
+            .unwrap_or_default();
+        entries.push(Entry {
+            id: format!("github:{}", n.notification_id),
+            kind: "notification",
+            title: n
+                .subject_title
+                .clone()
+                .unwrap_or_else(|| "New activity".to_string()),
+            subtitle: format!("GitHub · {}/{}{}", owner, repo, reason),
+            actions: vec!["open", "markRead"],
+            url: Some(
+                n.subject_url
+                    .as_deref()
+                    .map(notifications::web_url)
+                    .unwrap_or_else(|| format!("https://github.com/{}/{}", owner, repo)),
+            ),
+            target: Target::GithubNotification {
+                id: n.notification_id.clone(),
+            },
+        });
+    }
+
+    for n in &data.shortcut_notifications {
+        let story = n.story_id.and_then(|id| {
+            data.shortcut_stories
+                .iter()
+                .find(|story| story.story_id == id)
+        });
+        let subtitle = match (story, n.actor_name.as_deref()) {
+            (Some(story), _) => format!("Shortcut · {}", story.name),
+            (None, Some(actor)) => format!("Shortcut · {}", actor),
+            (None, None) => "Shortcut".to_string(),
+        };
+        entries.push(Entry {
+            id: format!("shortcut:{}", n.notification_id),
+            kind: "notification",
+            title: n
+                .message
+                .clone()
+                .unwrap_or_else(|| "New activity".to_string()),
+            subtitle,
+            actions: vec!["open", "markRead"],
+            url: Some(
+                n.story_id
+                    .map(story_url)
+                    .unwrap_or_else(|| "https://app.shortcut.com".to_string()),
+            ),
+            target: Target::ShortcutNotification {
+                id: n.notification_id.clone(),
+            },
+        });
+    }
+
+    entries
+}
+
+fn story_url(id: i64) -> String {
+    format!("https://app.shortcut.com/story/{}", id)
+}
+
+// Fuzzy match: every character of the query must appear in order. Runs of
+// consecutive characters and matches at the start of a word count for more,
+// so "mcr" ranks "Mission Control Release" above "macro".
+fn score(query: &str, text: &str) -> Option<i64> {
+    let text: Vec<char> = text.to_lowercase().chars().collect();
+    let mut score = 0;
+    let mut next = 0;
+    let mut previous: Option<usize> = None;
+
+    for wanted in query.to_lowercase().chars().filter(|c| !c.is_whitespace()) {
+        let found = (next..text.len()).find(|&i| text[i] == wanted)?;
+        score += 1;
+        if found > 0 && previous == Some(found - 1) {
+            score += 5;
+        }
+        if found == 0 || !text[found - 1].is_alphanumeric() {
+            score += 3;
+        }
+        previous = Some(found);
+        next = found + 1;
+    }
+    Some(score)
+}
+
+// Same prompts as the dashboard's Claude buttons
+fn prompt(target: &Target) -> Option<String> {
+    match target {
+        Target::Pr { url } => Some(format!(
+            "Review this PR: {}
+
+Please analyze:
+1. Code quality and potential bugs
+2. Architecture and design patterns
+3. Test coverage
+4. Security considerations
+
+Fetch the PR diff and any related context using your GitHub MCP tools, then provide specific, actionable feedback.",
+            url
+        )),
+        Target::Story { id, name } => Some(format!(
+            "Help me plan the implementation for this Shortcut story: #{id} - {name}
+
+Please:
+1. Open the story in Shortcut to understand the requirements and acceptance criteria
+2. Analyze what needs to be built or fixed
+3. Plan the implementation approach - consider:
+   - What files/components will need to be modified
+   - Any architectural decisions or patterns to follow
+   - Edge cases to handle
+   - Testing approach
+4. If helpful, check the ~/git/web codebase for relevant context or similar implementations
+
+Story URL: https://app.shortcut.com/story/{id}
+
+Provide a clear implementation plan I can follow to complete this ticket.",
+            id = id,
+            name = name
+        )),
+        _ => None,
+    }
+}
+
+// Show the palette over whatever the user is doing
+pub fn show(app: &AppHandle) {
+    if let Some(window) = app.get_window(WINDOW) {
+        let _ = window.center();
+        let _ = window.show();
+        let _ = window.set_focus();
+    }
+}
+
+// Tauri commands for the palette window
+#[tauri::command]
+pub fn search_palette(palette: tauri::State<'_, Palette>, query: String) -> Vec<Entry> {
+    palette.search(&query)
+}
+
+#[tauri::command]
+pub async fn run_palette_action(
+    app: AppHandle,
+    id: String,
+    action: String,
+) -> Result<(), ShellError> {
+    let palette = app.state::<Palette>();
+    let entry = palette.get(&id).ok_or_else(|| {
+        ShellError::Invalid("That item is no longer on the dashboard".to_string())
+    })?;
+    if !entry.actions.contains(&action.as_str()) {
+        return Err(ShellError::Invalid(format!(
+            "Can't {} a {}",
+            action, entry.kind
+        )));
+    }
+
+    // Fetched again afterwards so the dashboard and tray catch up
+    let mut refresh = None;
+    match (action.as_str(), &entry.target) {
+        ("open", _) => match &entry.url {
+            Some(url) => external::open(&app, url)?,
+            None => tray::show_window(&app),
+        },
+        ("copyPrompt", target) => {
+            let text = prompt(target).unwrap_or_default();
+            app.clipboard_manager().write_text(text).map_err(|e| {
+                ShellError::LaunchFailed(format!("Could not copy the prompt: {}", e))
+            })?;
+        }
+        ("complete", Target::Task { id }) => {
+            post(&format!("/api/todoist/tasks/{}/complete", id)).await?;
+            refresh = Some("todoist");
+        }
+        ("markRead", Target::GithubNotification { id }) => {
+            post(&format!("/api/notifications/github/{}/read", id)).await?;
+            refresh = Some("github");
+        }
+        ("markRead", Target::ShortcutNotification { id }) => {
+            post(&format!("/api/notifications/shortcut/{}/read", id)).await?;
+            refresh = Some("shortcut");
+        }
+        _ => {}
+    }
+
+    if let Some(window) = app.get_window(WINDOW) {
+        let _ = window.hide();
+    }
+    if let Some(service) = refresh {
+        palette.remove(&id);
+        let handle = app.clone();
+        tauri::async_runtime::spawn(async move {
+            let _ = sync::refresh(&handle, service).await;
+        });
+    }
+    Ok(())
+}
+
+async fn post(path: &str) -> Result<(), ShellError> {
+    #[derive(serde::Deserialize)]
+    struct Response {
+        error: Option<String>,
+    }
+
+    let response = reqwest::Client::new()
+        .post(server::url(path))
+        .send()
+        .await?;
+    let status = response.status();
+    if status.is_success() {
+        return Ok(());
+    }
+    let error = response
+        .json::<Response>()
+        .await
+        .ok()
+        .and_then(|body| body.error)
+        .unwrap_or_else(|| format!("Server answered {}", status));
+    Err(ShellError::ServerUnavailable(error))
+}
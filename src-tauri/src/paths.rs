@@ -0,0 +1,215 @@
+use serde::Serialize;
+use std::env;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+const APP_DIR: &str = "mission-control";
+const LEGACY_DIR: &str = ".mission-control";
+
+// Where the desktop app keeps its files
+pub struct AppPaths {
+    // config.json, desktop.json and the instance lock
+    pub config_dir: PathBuf,
+    // The SQLite database and server logs
+    pub data_dir: PathBuf,
+    // Anything the server can rebuild, safe to delete
+    pub cache_dir: PathBuf,
+}
+
+// Every location the shell resolved, as reported to the settings page
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ResolvedPaths {
+    config_dir: PathBuf,
+    config_file: PathBuf,
+    data_dir: PathBuf,
+    database: PathBuf,
+    logs_dir: PathBuf,
+    cache_dir: PathBuf,
+}
+
+impl AppPaths {
+    // macOS keeps the ~/.mission-control folder it always used. Linux follows
+    // the XDG base directory spec and Windows uses the roaming/local AppData split.
+    pub fn resolve() -> Self {
+        let home = home_dir();
+
+        if cfg!(target_os = "macos") {
+            let dir = home
+                .map(|home| home.join(LEGACY_DIR))
+                .unwrap_or_else(|| PathBuf::from(LEGACY_DIR));
+            return AppPaths {
+                config_dir: dir.clone(),
+                data_dir: dir.clone(),
+                cache_dir: dir.join("cache"),
+            };
+        }
+
+        // Without a home directory there is nowhere better than the old relative path
+        let fallback = PathBuf::from(LEGACY_DIR);
+        let app_dir = |base: Option<PathBuf>| {
+            base.map(|base| base.join(APP_DIR))
+                .unwrap_or_else(|| fallback.clone())
+        };
+
+        if cfg!(windows) {
+            let data_dir = app_dir(env_dir("LOCALAPPDATA"));
+            return AppPaths {
+                config_dir: app_dir(env_dir("APPDATA")),
+                cache_dir: data_dir.join("cache"),
+                data_dir,
+            };
+        }
+
+        let config = home.as_ref().map(|home| home.join(".config"));
+        let data = home.as_ref().map(|home| home.join(".local/share"));
+        let cache = home.as_ref().map(|home| home.join(".cache"));
+        AppPaths {
+            config_dir: app_dir(env_dir("XDG_CONFIG_HOME").or(config)),
+            data_dir: app_dir(env_dir("XDG_DATA_HOME").or(data)),
+            cache_dir: app_dir(env_dir("XDG_CACHE_HOME").or(cache)),
+        }
+    }
+
+    pub fn config_file(&self) -> PathBuf {
+        self.config_dir.join("config.json")
+    }
+
+    pub fn database(&self) -> PathBuf {
+        self.data_dir.join("mission-control.db")
+    }
+
+    pub fn logs_dir(&self) -> PathBuf {
+        self.data_dir.join("logs")
+    }
+
+    pub fn create(&self) {
+        for dir in [&self.config_dir, &self.data_dir, &self.cache_dir] {
+            if let Err(e) = fs::create_dir_all(dir) {
+                println!("Could not create {:?}: {}", dir, e);
+            }
+        }
+    }
+
+    // Move a .mission-control folder left by older builds (in the home
+    // directory, or wherever the launcher's cwd happened to be) into the new
+    // locations. Runs only until the new config directory has a config.json.
+    pub fn migrate_legacy(&self) {
+        if self.config_file().exists() {
+            return;
+        }
+
+        let mut candidates = Vec::new();
+        if let Ok(cwd) = env::current_dir() {
+            candidates.push(cwd.join(LEGACY_DIR));
+        }
+        if let Some(home) = home_dir() {
+            candidates.push(home.join(LEGACY_DIR));
+        }
+
+        for legacy in candidates {
+            if !legacy.is_dir() || same_dir(&legacy, &self.config_dir) {
+                continue;
+            }
+
+            println!("Migrating {:?} to {:?}", legacy, self.config_dir);
+            if let Err(e) = self.migrate_from(&legacy) {
+                println!("Migration from {:?} incomplete: {}", legacy, e);
+            }
+            // Leaves anything that could not be moved in place
+            let _ = fs::remove_dir(&legacy);
+            return;
+        }
+    }
+
+    fn migrate_from(&self, legacy: &Path) -> io::Result<()> {
+        for entry in fs::read_dir(legacy)? {
+            let entry = entry?;
+            let name = entry.file_name();
+            let name_str = name.to_string_lossy();
+
+            let target = if name_str == "instance.lock" {
+                // Belongs to a process that has already exited
+                let _ = fs::remove_file(entry.path());
+                continue;
+            } else if name_str == "logs" || name_str.ends_with(".db") {
+                self.data_dir.join(&name)
+            } else {
+                self.config_dir.join(&name)
+            };
+
+            move_entry(&entry.path(), &target)?;
+        }
+        Ok(())
+    }
+
+    // Hand the locations to server.js, which otherwise writes next to its own sources
+    pub fn export(&self) {
+        env::set_var("MISSION_CONTROL_CONFIG", self.config_file());
+        env::set_var("MISSION_CONTROL_DATA_DIR", &self.data_dir);
+        env::set_var("MISSION_CONTROL_DB", self.database());
+        env::set_var("MISSION_CONTROL_LOG_DIR", self.logs_dir());
+        env::set_var("MISSION_CONTROL_CACHE_DIR", &self.cache_dir);
+    }
+
+    pub fn report(&self) -> ResolvedPaths {
+        ResolvedPaths {
+            config_dir: self.config_dir.clone(),
+            config_file: self.config_file(),
+            data_dir: self.data_dir.clone(),
+            database: self.database(),
+            logs_dir: self.logs_dir(),
+            cache_dir: self.cache_dir.clone(),
+        }
+    }
+}
+
+#[tauri::command]
+pub fn get_app_paths(paths: tauri::State<'_, AppPaths>) -> ResolvedPaths {
+    paths.report()
+}
+
+// Rename when possible, copy and delete across filesystems. Never overwrites
+// something already at the destination.
+fn move_entry(from: &Path, to: &Path) -> io::Result<()> {
+    if to.exists() {
+        println!("Keeping existing {:?}; not migrating {:?}", to, from);
+        return Ok(());
+    }
+    if fs::rename(from, to).is_ok() {
+        return Ok(());
+    }
+
+    if from.is_dir() {
+        fs::create_dir_all(to)?;
+        for entry in fs::read_dir(from)? {
+            let entry = entry?;
+            move_entry(&entry.path(), &to.join(entry.file_name()))?;
+        }
+        fs::remove_dir(from)
+    } else {
+        fs::copy(from, to)?;
+        fs::remove_file(from)
+    }
+}
+
+fn same_dir(a: &Path, b: &Path) -> bool {
+    match (a.canonicalize(), b.canonicalize()) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => a == b,
+    }
+}
+
+// XDG variables must hold absolute paths; anything else is ignored per the spec
+fn env_dir(name: &str) -> Option<PathBuf> {
+    env::var_os(name)
+        .map(PathBuf::from)
+        .filter(|path| path.is_absolute())
+}
+
+pub fn home_dir() -> Option<PathBuf> {
+    env::var_os("HOME")
+        .or_else(|| env::var_os("USERPROFILE"))
+        .map(PathBuf::from)
+}
@@ -0,0 +1,267 @@
+use crate::error::ShellError;
+use crate::server::{self, ServerProcess, ServerState};
+use crate::sync;
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager};
+
+pub const WINDOW: &str = "quick-add";
+
+const WEEKDAYS: [&str; 7] = [
+    "monday",
+    "tuesday",
+    "wednesday",
+    "thursday",
+    "friday",
+    "saturday",
+    "sunday",
+];
+
+// Also ordinary words ("sun cream", "sat solver"), so only taken as a date
+// after "due", "by" or "on", or at the end of the task
+const SHORT_WEEKDAYS: [&str; 7] = ["mon", "tue", "wed", "thu", "fri", "sat", "sun"];
+
+// A task typed into the quick-add window, with its hints pulled out
+#[derive(Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QuickTask {
+    pub content: String,
+    // Natural language for Todoist to interpret, e.g. "next friday"
+    pub due_string: Option<String>,
+    // An explicit YYYY-MM-DD
+    pub due_date: Option<String>,
+    // As typed: 1 is most urgent, like Todoist's p1
+    pub priority: Option<u8>,
+}
+
+#[derive(Serialize)]
+struct CreateTask<'a> {
+    content: &'a str,
+    due_string: Option<&'a str>,
+    due_date: Option<&'a str>,
+    // Todoist's API counts the other way: 4 is most urgent
+    priority: Option<u8>,
+}
+
+#[derive(Deserialize)]
+struct CreateResponse {
+    error: Option<String>,
+}
+
+// Split "Send invoice tomorrow p2" into the task and its hints. Priority is a
+// p1..p4 word; a due date is an ISO date, or today, tomorrow, a weekday, "next
+// week" or "in 3 days", optionally after "due", "by" or "on". Weekday
+// abbreviations need that lead-in or to come last, priorities aside.
+pub fn parse(input: &str) -> QuickTask {
+    let words: Vec<&str> = input.split_whitespace().collect();
+    let mut task = QuickTask::default();
+    let mut content = Vec::new();
+
+    let mut i = 0;
+    while i < words.len() {
+        let word = words[i].to_ascii_lowercase();
+        if let Some(priority) = priority(&word) {
+            task.priority = Some(priority);
+            i += 1;
+            continue;
+        }
+        if task.due_string.is_none() && task.due_date.is_none() {
+            // Skip a leading "due", "by" or "on" if a date follows it
+            let lead = usize::from(matches!(word.as_str(), "due" | "by" | "on"));
+            let rest: Vec<String> = words[i + lead..]
+                .iter()
+                .map(|word| word.to_ascii_lowercase())
+                .collect();
+            // Nothing but priorities after the phrase counts as the end
+            let at_end = |len: usize| {
+                words[i + lead + len..]
+                    .iter()
+                    .all(|word| priority(&word.to_ascii_lowercase()).is_some())
+            };
+            let found = due_phrase(&rest, lead == 1)
+                .or_else(|| due_phrase(&rest, true).filter(|&len| at_end(len)));
+            if let Some(len) = found {
+                let phrase = rest[..len].join(" ");
+                if is_iso_date(&phrase) {
+                    task.due_date = Some(phrase);
+                } else {
+                    task.due_string = Some(phrase);
+                }
+                i += lead + len;
+                continue;
+            }
+        }
+        content.push(words[i]);
+        i += 1;
+    }
+
+    task.content = content.join(" ");
+    task
+}
+
+fn priority(word: &str) -> Option<u8> {
+    match word {
+        "p1" => Some(1),
+        "p2" => Some(2),
+        "p3" => Some(3),
+        "p4" => Some(4),
+        _ => None,
+    }
+}
+
+// Number of words at the start of `words` that make up a due date. `short`
+// lets weekday abbreviations count.
+fn due_phrase(words: &[String], short: bool) -> Option<usize> {
+    let word = |i: usize| words.get(i).map(String::as_str).unwrap_or_default();
+    let weekday = |day: &str| WEEKDAYS.contains(&day) || (short && SHORT_WEEKDAYS.contains(&day));
+    match word(0) {
+        "today" | "tonight" | "tomorrow" => Some(1),
+        day if weekday(day) || is_iso_date(day) => Some(1),
+        "next" | "this" if weekday(word(1)) => Some(2),
+        "next" if matches!(word(1), "week" | "month") => Some(2),
+        "in" if word(1).parse::<u32>().is_ok()
+            && matches!(word(2), "day" | "days" | "week" | "weeks") =>
+        {
+            Some(3)
+        }
+        _ => None,
+    }
+}
+
+fn is_iso_date(text: &str) -> bool {
+    let bytes = text.as_bytes();
+    bytes.len() == 10
+        && bytes.iter().enumerate().all(|(i, b)| match i {
+            4 | 7 => *b == b'-',
+            _ => b.is_ascii_digit(),
+        })
+}
+
+// Show the quick-add window over whatever the user is doing
+pub fn show(app: &AppHandle) {
+    if let Some(window) = app.get_window(WINDOW) {
+        let _ = window.center();
+        let _ = window.show();
+        let _ = window.set_focus();
+    }
+}
+
+// Tauri commands for the quick-add window: a live preview of the hints, and
+// submitting the task to the running server
+#[tauri::command]
+pub fn parse_quick_task(input: String) -> QuickTask {
+    parse(&input)
+}
+
+#[tauri::command]
+pub async fn add_quick_task(app: AppHandle, input: String) -> Result<QuickTask, ShellError> {
+    let task = parse(&input);
+    if task.content.is_empty() {
+        return Err(ShellError::Invalid("Type a task first".to_string()));
+    }
+    if !matches!(
+        app.state::<ServerProcess>().state(),
+        ServerState::Ready { .. }
+    ) {
+        return Err(ShellError::ServerUnavailable(
+            "Mission Control's server isn't running, so the task can't be sent to Todoist"
+                .to_string(),
+        ));
+    }
+
+    let response = reqwest::Client::new()
+        .post(server::url("/api/todoist/tasks"))
+        .json(&CreateTask {
+            content: &task.content,
+            due_string: task.due_string.as_deref(),
+            due_date: task.due_date.as_deref(),
+            priority: task.priority.map(|p| 5 - p),
+        })
+        .send()
+        .await?;
+    let status = response.status();
+    if !status.is_success() {
+        let error = response
+            .json::<CreateResponse>()
+            .await
+            .ok()
+            .and_then(|body| body.error)
+            .unwrap_or_else(|| format!("Server answered {}", status));
+        return Err(if error == "Todoist not configured" {
+            ShellError::NotAllowed(
+                "Todoist isn't set up. Add an API token on the Settings page.".to_string(),
+            )
+        } else {
+            ShellError::ServerUnavailable(format!("Todoist didn't take the task: {}", error))
+        });
+    }
+
+    if let Some(window) = app.get_window(WINDOW) {
+        let _ = window.hide();
+    }
+    // Fetch Todoist so the new task shows up on the dashboard and in the tray
+    let handle = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let _ = sync::refresh(&handle, "todoist").await;
+    });
+    Ok(task)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::parse;
+
+    #[test]
+    fn pulls_out_due_date_and_priority() {
+        let task = parse("Send invoice tomorrow p2");
+        assert_eq!(task.content, "Send invoice");
+        assert_eq!(task.due_string.as_deref(), Some("tomorrow"));
+        assert_eq!(task.priority, Some(2));
+    }
+
+    #[test]
+    fn reads_multi_word_and_iso_dates() {
+        let task = parse("Renew passport due next friday");
+        assert_eq!(task.content, "Renew passport");
+        assert_eq!(task.due_string.as_deref(), Some("next friday"));
+
+        let task = parse("Water plants in 3 days");
+        assert_eq!(task.content, "Water plants");
+        assert_eq!(task.due_string.as_deref(), Some("in 3 days"));
+
+        let task = parse("File taxes by 2025-03-01");
+        assert_eq!(task.content, "File taxes");
+        assert_eq!(task.due_date.as_deref(), Some("2025-03-01"));
+        assert_eq!(task.due_string, None);
+    }
+
+    #[test]
+    fn leaves_weekday_abbreviations_in_the_text() {
+        let task = parse("Buy sun cream");
+        assert_eq!(task.content, "Buy sun cream");
+        assert_eq!(task.due_string, None);
+
+        let task = parse("Fix sat solver p1");
+        assert_eq!(task.content, "Fix sat solver");
+        assert_eq!(task.due_string, None);
+        assert_eq!(task.priority, Some(1));
+    }
+
+    #[test]
+    fn takes_weekday_abbreviations_after_a_lead_in_or_at_the_end() {
+        let task = parse("Book dentist on wed for the kids");
+        assert_eq!(task.content, "Book dentist for the kids");
+        assert_eq!(task.due_string.as_deref(), Some("wed"));
+
+        let task = parse("Call mum sun p3");
+        assert_eq!(task.content, "Call mum");
+        assert_eq!(task.due_string.as_deref(), Some("sun"));
+        assert_eq!(task.priority, Some(3));
+    }
+
+    #[test]
+    fn keeps_only_the_first_due_date() {
+        let task = parse("Plan today for tomorrow");
+        assert_eq!(task.content, "Plan for tomorrow");
+        assert_eq!(task.due_string.as_deref(), Some("today"));
+    }
+}
@@ -0,0 +1,227 @@
+use crate::external;
+use crate::feed::{CalendarEvent, DashboardData, Feed};
+use crate::notify::{self, Action};
+use crate::settings::Settings;
+use crate::tray;
+use chrono::{DateTime, Duration as Span, Local, Utc};
+use std::collections::{HashMap, HashSet};
+use std::time::Duration;
+use tauri::{AppHandle, Manager, Url};
+use tokio::sync::mpsc;
+
+// How often upcoming meetings are checked against the reminder offsets
+const TICK: Duration = Duration::from_secs(15);
+
+// A reminder is still worth showing this long after the meeting started
+const GRACE_MINS: i64 = 2;
+
+const SNOOZE_MINS: i64 = 5;
+
+// Video call services whose links get a Join button; external::open allows
+// them too
+pub const MEETING_HOSTS: [&str; 6] = [
+    "zoom.us",
+    "meet.google.com",
+    "teams.microsoft.com",
+    "teams.live.com",
+    "webex.com",
+    "whereby.com",
+];
+
+const JOIN: &str = "join";
+const SNOOZE: &str = "snooze";
+
+// A timed event from today's calendar, ready to remind about
+struct Meeting {
+    uid: String,
+    title: String,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+    location: Option<String>,
+    link: Option<String>,
+}
+
+// Remind about upcoming meetings at the configured offsets
+pub fn watch(app: &AppHandle) {
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let updates = app.state::<Feed>().subscribe();
+        let (snooze_tx, mut snoozes) = mpsc::unbounded_channel::<String>();
+        // (uid, offset) pairs already reminded about, and snoozed uids with
+        // the time to remind again
+        let mut fired: HashSet<(String, u32)> = HashSet::new();
+        let mut snoozed: HashMap<String, DateTime<Utc>> = HashMap::new();
+        let mut ticker = tokio::time::interval(TICK);
+
+        loop {
+            tokio::select! {
+                _ = ticker.tick() => {}
+                Some(uid) = snoozes.recv() => {
+                    snoozed.insert(uid, Utc::now() + Span::minutes(SNOOZE_MINS));
+                    continue;
+                }
+            }
+
+            let Some(data) = updates.borrow().clone() else {
+                continue;
+            };
+            let settings = app.state::<Settings>().get();
+            let mut offsets = settings.reminder_offsets_mins;
+            offsets.sort_unstable_by(|a, b| b.cmp(a));
+            offsets.dedup();
+
+            let now = Utc::now();
+            let meetings = meetings(&data, &settings.muted_calendars);
+            fired.retain(|(uid, _)| meetings.iter().any(|m| m.uid == *uid));
+            snoozed.retain(|uid, _| meetings.iter().any(|m| m.uid == *uid && now < m.end));
+
+            for meeting in &meetings {
+                let snooze_over = snoozed.get(&meeting.uid).is_some_and(|at| now >= *at);
+                if snooze_over {
+                    snoozed.remove(&meeting.uid);
+                }
+
+                // Every offset whose time has come; only one reminder is shown
+                // even if the app started late and several are due at once
+                let due: Vec<u32> = offsets
+                    .iter()
+                    .copied()
+                    .filter(|offset| {
+                        now >= meeting.start - Span::minutes(i64::from(*offset))
+                            && now < meeting.start + Span::minutes(GRACE_MINS)
+                            && !fired.contains(&(meeting.uid.clone(), *offset))
+                    })
+                    .collect();
+                fired.extend(due.iter().map(|offset| (meeting.uid.clone(), *offset)));
+
+                if snooze_over || !due.is_empty() {
+                    remind(&app, meeting, now, snooze_tx.clone());
+                }
+            }
+        }
+    });
+}
+
+fn remind(
+    app: &AppHandle,
+    meeting: &Meeting,
+    now: DateTime<Utc>,
+    snooze: mpsc::UnboundedSender<String>,
+) {
+    let mut lines = vec![format!(
+        "{} ({})",
+        when(meeting.start, now),
+        meeting.start.with_timezone(&Local).format("%H:%M")
+    )];
+    if let Some(location) = &meeting.location {
+        lines.push(location.clone());
+    }
+
+    let mut actions = Vec::new();
+    if meeting.link.is_some() {
+        actions.push(Action {
+            id: JOIN,
+            label: "Join",
+        });
+    }
+    actions.push(Action {
+        id: SNOOZE,
+        label: "Snooze 5 min",
+    });
+
+    let app = app.clone();
+    let uid = meeting.uid.clone();
+    let link = meeting.link.clone();
+    notify::show(
+        meeting.title.clone(),
+        lines.join("\n"),
+        actions,
+        move |action| match action {
+            JOIN => {
+                if let Some(link) = link {
+                    let _ = external::open(&app, &link);
+                }
+            }
+            SNOOZE => {
+                let _ = snooze.send(uid);
+            }
+            _ => tray::show_window(&app),
+        },
+    );
+}
+
+fn when(start: DateTime<Utc>, now: DateTime<Utc>) -> String {
+    let plural = |n: i64| if n == 1 { "" } else { "s" };
+    let seconds = (start - now).num_seconds();
+    if seconds > 0 {
+        let minutes = (seconds + 59) / 60;
+        format!("Starts in {} minute{}", minutes, plural(minutes))
+    } else if seconds > -60 {
+        "Starting now".to_string()
+    } else {
+        let minutes = -seconds / 60;
+        format!("Started {} minute{} ago", minutes, plural(minutes))
+    }
+}
+
+// Today's timed events from calendars that aren't muted. All-day events have
+// nothing to join and no meaningful start, so they are left out.
+fn meetings(data: &DashboardData, muted: &[String]) -> Vec<Meeting> {
+    data.calendar
+        .iter()
+        .filter(|event| {
+            !event
+                .calendar_type
+                .as_ref()
+                .is_some_and(|calendar| muted.contains(calendar))
+        })
+        .filter_map(meeting)
+        .filter(|meeting| meeting.end - meeting.start < Span::hours(24))
+        .collect()
+}
+
+fn meeting(event: &CalendarEvent) -> Option<Meeting> {
+    let parse = |time: &str| {
+        DateTime::parse_from_rfc3339(time)
+            .ok()
+            .map(|time| time.with_timezone(&Utc))
+    };
+    let start = parse(&event.start_time)?;
+    let end = event.end_time.as_deref().and_then(parse).unwrap_or(start);
+
+    let location = event.location.as_deref().unwrap_or_default();
+    let description = event.description.as_deref().unwrap_or_default();
+    let link = meeting_link(location).or_else(|| meeting_link(description));
+
+    // A bare link makes a poor location line; the Join button covers it
+    let location = Some(location.trim())
+        .filter(|location| !location.is_empty() && !location.starts_with("https://"))
+        .map(str::to_string);
+
+    Some(Meeting {
+        uid: event.uid.clone(),
+        title: event.summary.clone(),
+        start,
+        end,
+        location,
+        link,
+    })
+}
+
+// First video call link in free text, e.g. an invite body
+pub fn meeting_link(text: &str) -> Option<String> {
+    text.split(|c: char| c.is_whitespace() || "<>\"'()[]".contains(c))
+        .filter_map(|word| word.find("https://").map(|at| &word[at..]))
+        .map(|url| url.trim_end_matches(['.', ',', ';']))
+        .find(|url| {
+            Url::parse(url)
+                .ok()
+                .and_then(|url| url.host_str().map(str::to_ascii_lowercase))
+                .is_some_and(|host| {
+                    MEETING_HOSTS
+                        .iter()
+                        .any(|domain| host == *domain || host.ends_with(&format!(".{}", domain)))
+                })
+        })
+        .map(str::to_string)
+}
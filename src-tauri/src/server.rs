@@ -0,0 +1,748 @@
+use crate::logs::ServerLogs;
+use crate::node::{self, NodeRuntime};
+use crate::startup;
+use crate::vault::{self, Vault};
+use serde::Serialize;
+use std::env;
+use std::io;
+use std::net::{Ipv6Addr, SocketAddr, TcpListener, TcpStream};
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, Stdio};
+use std::sync::atomic::{AtomicBool, AtomicU16, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use tauri::scope::ipc::RemoteDomainAccessScope;
+use tauri::utils::config::{AppUrl, WindowUrl};
+use tauri::{AppHandle, Manager, Url, Window};
+use tokio::sync::watch;
+
+// How often the supervisor checks whether node is still alive
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+// Restart backoff doubles from the initial delay up to the cap
+const INITIAL_BACKOFF: Duration = Duration::from_secs(1);
+const MAX_BACKOFF: Duration = Duration::from_secs(30);
+
+// A run that lasts this long is considered healthy and resets the backoff
+const STABLE_RUN: Duration = Duration::from_secs(60);
+
+// Stop restarting once the server has crashed this many times inside the window
+const CRASH_LOOP_LIMIT: usize = 5;
+const CRASH_LOOP_WINDOW: Duration = Duration::from_secs(120);
+
+// Only keep the most recent exits around
+const MAX_EXIT_HISTORY: usize = 50;
+
+// server.js prints this once express is listening
+const READY_MARKER: &str = "Mission Control running on port";
+
+// Something listening on ::1 answers right away; this only bounds odd setups
+const PROBE_TIMEOUT: Duration = Duration::from_millis(200);
+
+// Output lines attached to a failure so the splash screen can show them
+const FAILURE_OUTPUT_LINES: usize = 20;
+
+// How long server.js gets to stop the scheduler and close the database
+const SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(5);
+
+// Port used when nothing has been saved yet (same as the Docker/standalone server)
+pub const DEFAULT_PORT: u16 = 1337;
+
+// Commands the dashboard pages served by node call. The rest are for the
+// bundled splash, quick-add and palette pages only.
+const PAGE_COMMANDS: [&str; 11] = [
+    "open_external",
+    "get_shell_settings",
+    "update_shell_settings",
+    "get_app_paths",
+    "find_import_sources",
+    "preview_import",
+    "run_import",
+    "refresh_service",
+    "set_toggle_shortcut",
+    "set_quick_add_shortcut",
+    "set_palette_shortcut",
+];
+
+// Port chosen for this session, shared by everything that talks to the server.
+// Zero until choose_port runs; a retry after PortBusy may change it.
+static PORT: AtomicU16 = AtomicU16::new(0);
+
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ServerExit {
+    pub code: Option<i32>,
+    pub status: String,
+    pub exited_at: u64,
+    pub uptime_secs: u64,
+    pub restarting: bool,
+}
+
+#[derive(Clone, Copy, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum FailureReason {
+    NodeNotFound,
+    ServerNotFound,
+    SpawnFailed,
+    PortBusy,
+    Crashed,
+    TimedOut,
+}
+
+// What startup is busy with, for the splash page
+#[derive(Clone, Copy, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum StartupStep {
+    Port,
+    Vault,
+    Node,
+    Server,
+}
+
+#[derive(Clone, Serialize)]
+#[serde(tag = "state", rename_all = "camelCase")]
+pub enum ServerState {
+    Starting {
+        step: StartupStep,
+    },
+    Ready {
+        url: String,
+    },
+    #[serde(rename_all = "camelCase")]
+    Failed {
+        reason: FailureReason,
+        message: String,
+        output: Vec<String>,
+        restarting: bool,
+    },
+}
+
+// Why node could not be launched at all
+pub enum StartError {
+    NodeNotFound(String),
+    ServerNotFound(Vec<PathBuf>),
+    Spawn(io::Error),
+}
+
+impl StartError {
+    fn into_state(self) -> ServerState {
+        let (reason, message) = match self {
+            StartError::NodeNotFound(message) => (FailureReason::NodeNotFound, message),
+            StartError::ServerNotFound(searched) => (
+                FailureReason::ServerNotFound,
+                format!(
+                    "Could not find src/server.js in any of: {}",
+                    searched
+                        .iter()
+                        .map(|path| path.display().to_string())
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                ),
+            ),
+            StartError::Spawn(e) => (
+                FailureReason::SpawnFailed,
+                format!("Failed to spawn node: {}", e),
+            ),
+        };
+        ServerState::Failed {
+            reason,
+            message,
+            output: Vec::new(),
+            restarting: false,
+        }
+    }
+}
+
+pub struct ServerProcess {
+    child: Mutex<Option<Child>>,
+    started_at: Mutex<Instant>,
+    exits: Mutex<Vec<ServerExit>>,
+    crashes: Mutex<Vec<Instant>>,
+    stopping: AtomicBool,
+    // Set when restarting is pointless until the user asks to retry
+    gave_up: AtomicBool,
+    // Explicit node binary from the desktop settings, if any
+    node_path: Mutex<Option<PathBuf>>,
+    // The node found by the last start, so restarts don't search again
+    node: Mutex<Option<NodeRuntime>>,
+    // Counts calls to start, so a startup timeout can tell it's out of date
+    launches: AtomicU64,
+    // The API tokens for each spawn, when they're kept in the vault. Opened
+    // in the background at launch, so empty until then.
+    vault: Mutex<Option<Arc<Vault>>>,
+    logs: Arc<ServerLogs>,
+    state: Arc<watch::Sender<ServerState>>,
+}
+
+impl ServerProcess {
+    pub fn new(logs: Arc<ServerLogs>) -> Self {
+        ServerProcess {
+            child: Mutex::new(None),
+            started_at: Mutex::new(Instant::now()),
+            exits: Mutex::new(Vec::new()),
+            crashes: Mutex::new(Vec::new()),
+            stopping: AtomicBool::new(false),
+            gave_up: AtomicBool::new(false),
+            node_path: Mutex::new(None),
+            node: Mutex::new(None),
+            launches: AtomicU64::new(0),
+            vault: Mutex::new(None),
+            logs,
+            state: Arc::new(
+                watch::channel(ServerState::Starting {
+                    step: StartupStep::Port,
+                })
+                .0,
+            ),
+        }
+    }
+
+    pub fn state(&self) -> ServerState {
+        self.state.borrow().clone()
+    }
+
+    pub fn subscribe(&self) -> watch::Receiver<ServerState> {
+        self.state.subscribe()
+    }
+
+    pub fn set_state(&self, state: ServerState) {
+        self.state.send_replace(state);
+    }
+
+    // Move to Ready unless already there, so listeners only see the transition once
+    pub fn mark_ready(&self) {
+        mark_ready(&self.state);
+    }
+
+    pub fn logs(&self) -> &Arc<ServerLogs> {
+        &self.logs
+    }
+
+    pub fn vault(&self) -> Option<Arc<Vault>> {
+        self.vault.lock().unwrap().clone()
+    }
+
+    pub fn set_vault(&self, vault: Option<Arc<Vault>>) {
+        *self.vault.lock().unwrap() = vault;
+    }
+
+    pub fn set_node_path(&self, path: Option<PathBuf>) {
+        let mut current = self.node_path.lock().unwrap();
+        if *current != path {
+            *self.node.lock().unwrap() = None;
+            *current = path;
+        }
+    }
+
+    pub fn launch(&self) -> u64 {
+        self.launches.load(Ordering::SeqCst)
+    }
+
+    // Spawn node and start draining its output
+    pub fn start(&self) {
+        self.launches.fetch_add(1, Ordering::SeqCst);
+        self.set_state(ServerState::Starting {
+            step: StartupStep::Node,
+        });
+        let node_path = self.node_path.lock().unwrap().clone();
+        let spawned = find_root().and_then(|root| {
+            let node = self
+                .node_runtime(node_path.as_deref(), &root)
+                .map_err(StartError::NodeNotFound)?;
+            self.set_state(ServerState::Starting {
+                step: StartupStep::Server,
+            });
+            let secrets = self.vault().map(|vault| vault.server_env());
+            start_server(&node.path, &root, secrets.unwrap_or_default())
+        });
+        match spawned {
+            Ok(mut child) => {
+                let state = Arc::clone(&self.state);
+                self.logs.attach(&mut child, move |line| {
+                    if line.contains(READY_MARKER) {
+                        mark_ready(&state);
+                    }
+                });
+                self.gave_up.store(false, Ordering::SeqCst);
+                self.replace(Some(child));
+            }
+            Err(e) => {
+                // Nothing will change until the user fixes the install and retries
+                self.gave_up.store(true, Ordering::SeqCst);
+                self.replace(None);
+                self.set_state(e.into_state());
+            }
+        }
+    }
+
+    // The node used last time if it still works, otherwise a full search
+    fn node_runtime(&self, explicit: Option<&Path>, root: &Path) -> Result<NodeRuntime, String> {
+        let cached = self.node.lock().unwrap().take();
+        let runtime = match cached.map(|runtime| node::recheck(runtime, root)) {
+            Some(Ok(runtime)) => runtime,
+            Some(Err(reason)) => {
+                println!("Node.js is no longer usable, looking again: {}", reason);
+                node::resolve(explicit, root)?
+            }
+            None => node::resolve(explicit, root)?,
+        };
+        *self.node.lock().unwrap() = Some(runtime.clone());
+        Ok(runtime)
+    }
+
+    // User-requested restart: forget past crashes and start from scratch. If
+    // something else took the port, move to a free one. Returns the port used.
+    pub fn retry(&self) -> u16 {
+        let port_busy = matches!(
+            self.state(),
+            ServerState::Failed {
+                reason: FailureReason::PortBusy,
+                ..
+            }
+        );
+        self.restart_around(|| {
+            if port_busy {
+                choose_port(port())
+            } else {
+                port()
+            }
+        })
+    }
+
+    // Stop the server, run `work` while nothing has the database open, then
+    // start it again
+    pub fn restart_around<T>(&self, work: impl FnOnce() -> T) -> T {
+        self.gave_up.store(true, Ordering::SeqCst);
+        if let Some(child) = self.child.lock().unwrap().take() {
+            terminate(child, SHUTDOWN_TIMEOUT);
+        }
+        let result = work();
+        self.crashes.lock().unwrap().clear();
+        self.start();
+        result
+    }
+
+    // Last lines of server output, for attaching to failures
+    pub fn recent_output(&self) -> Vec<String> {
+        self.logs
+            .recent(FAILURE_OUTPUT_LINES)
+            .into_iter()
+            .map(|entry| entry.line)
+            .collect()
+    }
+
+    pub fn exits(&self) -> Vec<ServerExit> {
+        self.exits.lock().unwrap().clone()
+    }
+
+    pub fn is_stopping(&self) -> bool {
+        self.stopping.load(Ordering::SeqCst)
+    }
+
+    // Stop the server for good: SIGTERM the process group, then SIGKILL on timeout
+    pub fn shutdown(&self) {
+        self.stopping.store(true, Ordering::SeqCst);
+        if let Some(child) = self.child.lock().unwrap().take() {
+            terminate(child, SHUTDOWN_TIMEOUT);
+        }
+    }
+
+    fn replace(&self, child: Option<Child>) {
+        let mut current = self.child.lock().unwrap();
+        if self.is_stopping() {
+            // Shutdown started while we were restarting
+            if let Some(child) = child {
+                terminate(child, SHUTDOWN_TIMEOUT);
+            }
+            return;
+        }
+        if let Some(previous) = std::mem::replace(&mut *current, child) {
+            terminate(previous, SHUTDOWN_TIMEOUT);
+        }
+        *self.started_at.lock().unwrap() = Instant::now();
+    }
+
+    fn record_exit(&self, exit: ServerExit) {
+        let mut exits = self.exits.lock().unwrap();
+        exits.push(exit);
+        if exits.len() > MAX_EXIT_HISTORY {
+            let overflow = exits.len() - MAX_EXIT_HISTORY;
+            exits.drain(..overflow);
+        }
+    }
+
+    // Returns the exit status if the child has gone away since the last check
+    fn poll(&self) -> Option<std::process::ExitStatus> {
+        let mut child = self.child.lock().unwrap();
+        match child.as_mut() {
+            None => None,
+            Some(process) => match process.try_wait() {
+                Ok(Some(status)) => {
+                    *child = None;
+                    Some(status)
+                }
+                Ok(None) => None,
+                Err(e) => {
+                    println!("Failed to check server status: {}", e);
+                    None
+                }
+            },
+        }
+    }
+}
+
+// Pick the port for this session: the preferred one if it's free, otherwise
+// any free loopback port
+pub fn choose_port(preferred: u16) -> u16 {
+    let port = free_port(preferred)
+        .or_else(|| {
+            println!("Port {} is busy, picking another", preferred);
+            (0..5).find_map(|_| free_port(0))
+        })
+        .unwrap_or(preferred);
+
+    PORT.store(port, Ordering::SeqCst);
+    println!("Server port: {}", port);
+    port
+}
+
+// The server listens on 127.0.0.1, but the webview loads `localhost`, which
+// may resolve to ::1 first. A port something answers on there (e.g. a Docker
+// instance listening on *:1337) would show the wrong dashboard, so it's busy.
+fn free_port(port: u16) -> Option<u16> {
+    let port = TcpListener::bind(("127.0.0.1", port))
+        .ok()?
+        .local_addr()
+        .ok()?
+        .port();
+    let ipv6 = SocketAddr::from((Ipv6Addr::LOCALHOST, port));
+    if TcpStream::connect_timeout(&ipv6, PROBE_TIMEOUT).is_ok() {
+        return None;
+    }
+    Some(port)
+}
+
+pub fn port() -> u16 {
+    match PORT.load(Ordering::SeqCst) {
+        0 => DEFAULT_PORT,
+        port => port,
+    }
+}
+
+// Address of the local dashboard server, for the shell's own requests. Always
+// the IPv4 loopback the server is bound to, never whatever `localhost` means.
+pub fn url(path: &str) -> String {
+    format!("http://127.0.0.1:{}{}", port(), path)
+}
+
+// The same server as the webview loads it. Tauri's remote IPC scope matches
+// domain names only, so the pages need `localhost` to call the shell;
+// choose_port makes sure nothing else answers there on ::1. Since the scope
+// ignores the port, it still applies after a retry moves the server.
+pub fn page_url(path: &str) -> String {
+    format!("http://localhost:{}{}", port(), path)
+}
+
+// Tauri only answers IPC from its own origin, so let the main window reach
+// the shell once it has moved on to the dashboard. The Tauri API is needed for
+// event.listen; the allowlist in tauri.conf.json keeps the rest of it off.
+pub fn allow_page_ipc(app: &AppHandle) {
+    app.ipc_scope().configure_remote_access(
+        RemoteDomainAccessScope::new("localhost")
+            .allow_on_scheme("http")
+            .add_window("main")
+            .enable_tauri_api(),
+    );
+}
+
+// Whether the page in `window` may call `command`. The bundled pages may call
+// anything, dashboard pages only PAGE_COMMANDS, and nothing else gets in.
+pub fn page_may_call(window: &Window, command: &str) -> bool {
+    let page = window.url().origin();
+    if Url::parse(&page_url("/")).map_or(false, |dashboard| dashboard.origin() == page) {
+        PAGE_COMMANDS.contains(&command)
+    } else {
+        app_origin(window).origin() == page
+    }
+}
+
+// Where Tauri serves the bundled pages from, worked out the way it does: the
+// devPath server under `tauri dev`, otherwise its own protocol, which is
+// http(s)://tauri.localhost on Windows
+fn app_origin(window: &Window) -> Url {
+    let config = window.config();
+    #[cfg(dev)]
+    let base = &config.build.dev_path;
+    #[cfg(not(dev))]
+    let base = &config.build.dist_dir;
+    if let AppUrl::Url(WindowUrl::External(url)) = base {
+        return url.clone();
+    }
+    let origin = if !cfg!(windows) {
+        "tauri://localhost"
+    } else if config.tauri.security.dangerous_use_http_scheme {
+        "http://tauri.localhost"
+    } else {
+        "https://tauri.localhost"
+    };
+    Url::parse(origin).expect("app origin is a valid URL")
+}
+
+// Tauri command to inspect why the server has exited so far
+#[tauri::command]
+pub fn server_exits(process: tauri::State<'_, ServerProcess>) -> Vec<ServerExit> {
+    process.exits()
+}
+
+// Watch the node child and restart it with exponential backoff when it dies
+pub fn supervise(app: AppHandle) {
+    std::thread::spawn(move || {
+        let process = app.state::<ServerProcess>();
+        let mut backoff = INITIAL_BACKOFF;
+
+        loop {
+            std::thread::sleep(POLL_INTERVAL);
+            if process.is_stopping() {
+                break;
+            }
+            if process.gave_up.load(Ordering::SeqCst) {
+                continue;
+            }
+
+            let status = match process.poll() {
+                Some(status) => status,
+                None => continue,
+            };
+
+            let uptime = process.started_at.lock().unwrap().elapsed();
+            if uptime >= STABLE_RUN {
+                backoff = INITIAL_BACKOFF;
+            }
+
+            let crash_count = {
+                let mut crashes = process.crashes.lock().unwrap();
+                let now = Instant::now();
+                crashes.retain(|at| now.duration_since(*at) < CRASH_LOOP_WINDOW);
+                crashes.push(now);
+                crashes.len()
+            };
+            let restarting = crash_count < CRASH_LOOP_LIMIT;
+
+            let exit = ServerExit {
+                code: status.code(),
+                status: status.to_string(),
+                exited_at: unix_millis(),
+                uptime_secs: uptime.as_secs(),
+                restarting,
+            };
+            println!(
+                "Server exited ({}) after {}s",
+                exit.status, exit.uptime_secs
+            );
+            let message = format!("The server exited unexpectedly ({})", exit.status);
+            process.record_exit(exit);
+
+            let output = process.recent_output();
+            let reason = if output.iter().any(|line| line.contains("EADDRINUSE")) {
+                FailureReason::PortBusy
+            } else {
+                FailureReason::Crashed
+            };
+            process.set_state(ServerState::Failed {
+                reason,
+                message,
+                output,
+                restarting,
+            });
+
+            if !restarting {
+                println!(
+                    "Server crashed {} times in {:?}, giving up",
+                    crash_count, CRASH_LOOP_WINDOW
+                );
+                process.gave_up.store(true, Ordering::SeqCst);
+                continue;
+            }
+
+            println!("Restarting server in {:?}", backoff);
+            std::thread::sleep(backoff);
+            backoff = (backoff * 2).min(MAX_BACKOFF);
+            if process.is_stopping() {
+                break;
+            }
+            if process.gave_up.load(Ordering::SeqCst) {
+                continue;
+            }
+
+            process.start();
+            startup::watch(&app);
+        }
+    });
+}
+
+// Ask the server (and anything it spawned, like gh) to exit, escalating to a kill
+fn terminate(mut child: Child, timeout: Duration) {
+    let pid = child.id();
+    println!("Stopping server (PID {})", pid);
+    request_stop(&mut child);
+
+    let deadline = Instant::now() + timeout;
+    while Instant::now() < deadline {
+        match child.try_wait() {
+            Ok(Some(status)) => {
+                println!("Server stopped ({})", status);
+                // Clean up any grandchildren that outlived node
+                signal_group(pid, true);
+                return;
+            }
+            Ok(None) => std::thread::sleep(Duration::from_millis(100)),
+            Err(e) => {
+                println!("Failed to wait for server: {}", e);
+                break;
+            }
+        }
+    }
+
+    println!("Server did not stop within {:?}, killing it", timeout);
+    signal_group(pid, true);
+    let _ = child.kill();
+    let _ = child.wait();
+}
+
+#[cfg(unix)]
+fn request_stop(child: &mut Child) {
+    signal_group(child.id(), false);
+}
+
+// taskkill without /F only posts WM_CLOSE, which a windowless node never
+// sees, so server.js reads a shutdown line from stdin instead
+#[cfg(windows)]
+fn request_stop(child: &mut Child) {
+    use std::io::Write;
+    if let Some(mut stdin) = child.stdin.take() {
+        if let Err(e) = stdin.write_all(b"shutdown\n") {
+            println!("Failed to ask the server to stop: {}", e);
+        }
+    }
+}
+
+// The server is spawned as its own process group leader, so its PID is the group ID
+#[cfg(unix)]
+fn signal_group(pid: u32, force: bool) {
+    let signal = if force { libc::SIGKILL } else { libc::SIGTERM };
+    unsafe {
+        libc::kill(-(pid as libc::pid_t), signal);
+    }
+}
+
+// Only used to force-kill the tree; see request_stop for the graceful path
+#[cfg(windows)]
+fn signal_group(pid: u32, _force: bool) {
+    let pid = pid.to_string();
+    let _ = Command::new("taskkill")
+        .args(["/PID", pid.as_str(), "/T", "/F"])
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status();
+}
+
+fn mark_ready(state: &watch::Sender<ServerState>) {
+    state.send_if_modified(|current| {
+        if matches!(current, ServerState::Ready { .. }) {
+            return false;
+        }
+        *current = ServerState::Ready { url: page_url("") };
+        true
+    });
+}
+
+fn unix_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+// Directories that may hold src/server.js: the working directory in dev, the
+// bundle's Resources, or the repo root above target/<profile>
+pub fn server_roots(exe_dir: &Path) -> Vec<PathBuf> {
+    let mut roots = vec![
+        exe_dir.join("../Resources"),
+        exe_dir.to_path_buf(),
+        exe_dir.join("../../.."),
+    ];
+    if let Ok(cwd) = env::current_dir() {
+        roots.insert(0, cwd);
+    }
+    roots
+}
+
+// The directory holding src/server.js and the node_modules it runs with
+fn find_root() -> Result<PathBuf, StartError> {
+    let exe_path = env::current_exe().map_err(StartError::Spawn)?;
+    let exe_dir = exe_path.parent().unwrap_or(&exe_path).to_path_buf();
+
+    println!("Executable path: {:?}", exe_path);
+    println!("Executable dir: {:?}", exe_dir);
+
+    let possible_roots = server_roots(&exe_dir);
+    match possible_roots
+        .iter()
+        .find(|root| root.join("src/server.js").exists())
+    {
+        Some(root) => Ok(root.clone()),
+        None => {
+            println!(
+                "Could not find src/server.js in any of: {:?}",
+                possible_roots
+            );
+            Err(StartError::ServerNotFound(possible_roots))
+        }
+    }
+}
+
+fn start_server(
+    node: &Path,
+    root: &Path,
+    secrets: Vec<(&'static str, String)>,
+) -> Result<Child, StartError> {
+    let server_script = root.join("src/server.js");
+    println!("Found server at: {:?}", server_script);
+    println!("Working directory: {:?}", root);
+
+    let mut command = Command::new(node);
+    command
+        .arg(&server_script)
+        .current_dir(root)
+        .env("NODE_ENV", "production")
+        .env("PORT", port().to_string())
+        .env("HOST", "127.0.0.1")
+        .envs(secrets)
+        // Only the shell needs this, to open the vault
+        .env_remove(vault::PASSPHRASE_VAR)
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped());
+
+    // Put node in its own process group so shutdown reaches its children too
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::CommandExt;
+        command.process_group(0);
+    }
+    #[cfg(windows)]
+    command
+        .env("MISSION_CONTROL_STDIN_SHUTDOWN", "1")
+        .stdin(Stdio::piped());
+
+    let child = command.spawn().map_err(|e| {
+        println!("Failed to spawn node: {}", e);
+        if e.kind() == io::ErrorKind::NotFound {
+            StartError::NodeNotFound(format!("Could not run {}: {}", node.display(), e))
+        } else {
+            StartError::Spawn(e)
+        }
+    })?;
+
+    println!("Server started with PID: {:?}", child.id());
+    Ok(child)
+}
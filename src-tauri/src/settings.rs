@@ -0,0 +1,119 @@
+use crate::error::ShellError;
+use crate::vault::VaultBackend;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+// Desktop-only preferences, kept next to config.json but owned by the shell
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ShellSettings {
+    // Hide to the tray when the window is closed instead of quitting
+    pub close_to_tray: bool,
+    // Port the server used last time, tried first on the next launch
+    pub server_port: u16,
+    // How long to wait for the server to come up before giving up
+    pub startup_timeout_secs: u64,
+    // Explicit node binary; when unset the shell searches for one
+    pub node_path: Option<PathBuf>,
+    // Where the key for the API token vault comes from
+    pub vault: VaultBackend,
+    // Extra hosts external links may open, on top of the built-in ones
+    pub allowed_hosts: Vec<String>,
+    // Announce new GitHub and Shortcut notifications while the window is in the background
+    pub desktop_notifications: bool,
+    // Minutes before a meeting to remind about it; empty turns reminders off
+    pub reminder_offsets_mins: Vec<u32>,
+    // Calendar types ("personal", "work") that never get reminders
+    pub muted_calendars: Vec<String>,
+    // Which sources count towards the unread badge in the tray and window title
+    pub badge_github: bool,
+    pub badge_shortcut: bool,
+    pub badge_todoist: bool,
+    // Global shortcut that shows or hides the dashboard; empty for none
+    pub toggle_shortcut: String,
+    // Global shortcut that opens the quick-add Todoist window; empty for none
+    pub quick_add_shortcut: String,
+    // Global shortcut that opens the command palette; empty for none
+    pub palette_shortcut: String,
+}
+
+impl Default for ShellSettings {
+    fn default() -> Self {
+        ShellSettings {
+            close_to_tray: true,
+            server_port: crate::server::DEFAULT_PORT,
+            startup_timeout_secs: 30,
+            node_path: None,
+            vault: VaultBackend::Keyring,
+            allowed_hosts: Vec::new(),
+            desktop_notifications: true,
+            reminder_offsets_mins: vec![10, 1],
+            muted_calendars: Vec::new(),
+            badge_github: true,
+            badge_shortcut: true,
+            badge_todoist: true,
+            toggle_shortcut: "CmdOrCtrl+Shift+M".to_string(),
+            quick_add_shortcut: "CmdOrCtrl+Alt+N".to_string(),
+            palette_shortcut: "CmdOrCtrl+Alt+K".to_string(),
+        }
+    }
+}
+
+pub struct Settings {
+    path: PathBuf,
+    current: Mutex<ShellSettings>,
+}
+
+impl Settings {
+    pub fn load(config_dir: &Path) -> Self {
+        let path = config_dir.join("desktop.json");
+        let current = fs::read_to_string(&path)
+            .ok()
+            .and_then(|data| {
+                serde_json::from_str(&data)
+                    .map_err(|e| println!("Ignoring invalid {:?}: {}", path, e))
+                    .ok()
+            })
+            .unwrap_or_default();
+
+        Settings {
+            path,
+            current: Mutex::new(current),
+        }
+    }
+
+    pub fn get(&self) -> ShellSettings {
+        self.current.lock().unwrap().clone()
+    }
+
+    pub fn set(&self, settings: ShellSettings) -> Result<(), ShellError> {
+        let data = serde_json::to_string_pretty(&settings)?;
+        fs::write(&self.path, data)?;
+        *self.current.lock().unwrap() = settings;
+        Ok(())
+    }
+}
+
+// Tauri commands for the desktop section of the settings page
+#[tauri::command]
+pub fn get_shell_settings(settings: tauri::State<'_, Settings>) -> ShellSettings {
+    settings.get()
+}
+
+#[tauri::command]
+pub fn update_shell_settings(
+    settings: tauri::State<'_, Settings>,
+    mut update: ShellSettings,
+) -> Result<ShellSettings, ShellError> {
+    // Settings that decide what the shell runs or opens can only be changed
+    // in desktop.json, never by a page
+    let current = settings.get();
+    update.allowed_hosts = current.allowed_hosts;
+    update.node_path = current.node_path;
+    update.vault = current.vault;
+    update.server_port = current.server_port;
+    settings.set(update)?;
+    Ok(settings.get())
+}
@@ -0,0 +1,217 @@
+use crate::error::ShellError;
+use crate::paths::AppPaths;
+use crate::server::{self, FailureReason, ServerProcess, ServerState, StartupStep};
+use crate::settings::Settings;
+use crate::vault;
+use std::sync::OnceLock;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Manager, Window};
+
+// Fallback /health probe interval, in case the ready line never shows up
+const HEALTH_PROBE_INTERVAL: Duration = Duration::from_millis(500);
+
+// Bundled page (in distDir) shown while the server starts or after it fails
+pub const SPLASH_PAGE: &str = "splash.html";
+
+// The splash page's full URL differs between dev and bundled builds, so it's
+// remembered from the first time the window loads it
+static SPLASH_URL: OnceLock<String> = OnceLock::new();
+
+pub fn remember_splash(url: &str) {
+    if url.ends_with(SPLASH_PAGE) {
+        let _ = SPLASH_URL.set(url.to_string());
+    }
+}
+
+fn show_splash(window: &Window) {
+    if let Some(url) = SPLASH_URL.get() {
+        let _ = window.eval(&format!("window.location.replace('{}')", url));
+    }
+}
+
+// Pick the port, open the vault and start the server off the UI thread, so
+// the splash page is up and shows each step. Then give the server its
+// startup budget.
+pub fn launch(app: &AppHandle) {
+    let app = app.clone();
+    std::thread::spawn(move || {
+        let process = app.state::<ServerProcess>();
+        let settings = app.state::<Settings>();
+        let paths = app.state::<AppPaths>();
+
+        // Reuse last session's port when it's free so the webview origin stays stable
+        process.set_state(ServerState::Starting {
+            step: StartupStep::Port,
+        });
+        remember_port(&settings, server::choose_port(settings.get().server_port));
+
+        // Keep API tokens out of config.json; the server gets them at spawn time
+        process.set_state(ServerState::Starting {
+            step: StartupStep::Vault,
+        });
+        process.set_vault(vault::start(
+            &paths.config_dir,
+            &paths.config_file(),
+            settings.get().vault,
+        ));
+
+        process.start();
+        watch(&app);
+    });
+}
+
+// Save the port in use so the next launch tries it first
+fn remember_port(settings: &Settings, port: u16) {
+    if port != settings.get().server_port {
+        let mut updated = settings.get();
+        updated.server_port = port;
+        let _ = settings.set(updated);
+    }
+}
+
+// Give the server its startup budget, then report a timeout if it's still
+// starting. Called after every start, including the supervisor's restarts.
+pub fn watch(app: &AppHandle) {
+    let launch = app.state::<ServerProcess>().launch();
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let process = app.state::<ServerProcess>();
+        let budget = Duration::from_secs(app.state::<Settings>().get().startup_timeout_secs);
+
+        // A crash and restart since then gets its own watch and full budget
+        if !wait_until_ready(&process, budget).await
+            && process.launch() == launch
+            && matches!(process.state(), ServerState::Starting { .. })
+        {
+            process.set_state(ServerState::Failed {
+                reason: FailureReason::TimedOut,
+                message: format!(
+                    "The server did not respond within {} seconds",
+                    budget.as_secs()
+                ),
+                output: process.recent_output(),
+                restarting: false,
+            });
+        }
+    });
+}
+
+// Broadcast every server state change and move the main window between the
+// splash page and the dashboard
+pub fn follow(app: &AppHandle) {
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let mut states = app.state::<ServerProcess>().subscribe();
+        loop {
+            let state = states.borrow_and_update().clone();
+            let _ = app.emit_all("server-state", state.clone());
+
+            if let Some(window) = app.get_window("main") {
+                match &state {
+                    ServerState::Ready { url } => {
+                        let _ = window.eval(&format!("window.location.replace('{}')", url));
+                    }
+                    ServerState::Failed {
+                        restarting: false, ..
+                    } => show_splash(&window),
+                    _ => {}
+                }
+            }
+
+            if states.changed().await.is_err() {
+                break;
+            }
+        }
+    });
+}
+
+// Resolve once the server reports it is listening, or answers /health, within
+// the budget. Returns false on timeout or when the server has failed for good.
+async fn wait_until_ready(process: &ServerProcess, budget: Duration) -> bool {
+    let mut states = process.subscribe();
+    let announced = async {
+        loop {
+            match &*states.borrow_and_update() {
+                ServerState::Ready { .. } => return Some("stdout"),
+                ServerState::Failed {
+                    restarting: false, ..
+                } => return None,
+                _ => {}
+            }
+            if states.changed().await.is_err() {
+                return None;
+            }
+        }
+    };
+
+    let probed = async {
+        let client = reqwest::Client::new();
+        loop {
+            tokio::time::sleep(HEALTH_PROBE_INTERVAL).await;
+            let healthy = client
+                .get(server::url("/health"))
+                .send()
+                .await
+                .map(|response| response.status().is_success())
+                .unwrap_or(false);
+            if healthy {
+                process.mark_ready();
+                return Some("health probe");
+            }
+        }
+    };
+
+    let started = Instant::now();
+    let result = tokio::time::timeout(budget, async {
+        tokio::select! {
+            source = announced => source,
+            source = probed => source,
+        }
+    })
+    .await;
+
+    match result {
+        Ok(Some(source)) => {
+            println!(
+                "Server ready after {:?} (via {})",
+                started.elapsed(),
+                source
+            );
+            true
+        }
+        Ok(None) => {
+            println!("Server failed to start");
+            false
+        }
+        Err(_) => {
+            println!("Server not ready after {:?}", budget);
+            false
+        }
+    }
+}
+
+// Tauri commands used by the splash page
+#[tauri::command]
+pub fn server_state(process: tauri::State<'_, ServerProcess>) -> ServerState {
+    process.state()
+}
+
+#[tauri::command]
+pub fn retry_server(app: AppHandle) {
+    // Restarting can block while the old process shuts down
+    std::thread::spawn(move || {
+        let process = app.state::<ServerProcess>();
+        // Pick up a nodePath fixed since launch
+        let settings = app.state::<Settings>();
+        process.set_node_path(settings.get().node_path);
+        // Keep a port picked after PortBusy for next launch too
+        remember_port(&settings, process.retry());
+        watch(&app);
+    });
+}
+
+#[tauri::command]
+pub fn open_logs(process: tauri::State<'_, ServerProcess>) -> Result<(), ShellError> {
+    open::that(process.logs().dir())
+        .map_err(|e| ShellError::LaunchFailed(format!("Could not open the logs folder: {}", e)))
+}
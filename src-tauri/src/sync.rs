@@ -0,0 +1,225 @@
+use crate::error::ShellError;
+use crate::feed::Feed;
+use crate::server;
+use crate::tray;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::sync::Mutex;
+use tauri::{AppHandle, Manager};
+
+// Services the scheduler can fetch on demand, with their display names
+pub const SERVICES: [(&str, &str); 4] = [
+    ("github", "GitHub"),
+    ("shortcut", "Shortcut"),
+    ("todoist", "Todoist"),
+    ("calendar", "Calendar"),
+];
+pub const ALL: &str = "all";
+
+// Service name to ISO time of its last successful fetch, null if never
+pub type LastFetchTimes = HashMap<String, Option<String>>;
+
+// Sent to the pages as "refresh-progress" while a manual refresh runs
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Progress<'a> {
+    service: &'a str,
+    // "started", "finished" or "failed"
+    status: &'static str,
+    last_fetch_times: Option<&'a LastFetchTimes>,
+    error: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct FetchResponse {
+    last_fetch_times: Option<LastFetchTimes>,
+    error: Option<String>,
+}
+
+// When each service last synced and which refreshes are running, for the
+// tray menu
+#[derive(Default)]
+pub struct SyncState {
+    last_synced: Mutex<HashMap<String, DateTime<Utc>>>,
+    running: Mutex<HashSet<String>>,
+}
+
+impl SyncState {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn record(&self, times: &LastFetchTimes) {
+        let mut last_synced = self.last_synced.lock().unwrap();
+        for (service, time) in times {
+            let parsed = time
+                .as_deref()
+                .and_then(|time| DateTime::parse_from_rfc3339(time).ok());
+            if let Some(time) = parsed {
+                last_synced.insert(service.clone(), time.with_timezone(&Utc));
+            }
+        }
+    }
+
+    pub fn is_running(&self, service: &str) -> bool {
+        let running = self.running.lock().unwrap();
+        running.contains(service) || running.contains(ALL)
+    }
+
+    // "synced 3 min ago"; for ALL, the service that has gone longest
+    pub fn synced_ago(&self, service: &str) -> Option<String> {
+        let last_synced = self.last_synced.lock().unwrap();
+        let time = if service == ALL {
+            last_synced.values().min()
+        } else {
+            last_synced.get(service)
+        }?;
+        let minutes = (Utc::now() - *time).num_minutes();
+        Some(if minutes < 1 {
+            "synced just now".to_string()
+        } else {
+            format!("synced {} min ago", minutes)
+        })
+    }
+
+    fn start(&self, service: &str) -> bool {
+        self.running.lock().unwrap().insert(service.to_string())
+    }
+
+    fn finish(&self, service: &str) {
+        self.running.lock().unwrap().remove(service);
+    }
+}
+
+// Ask the server to fetch one service (or ALL) now, reporting progress to the
+// pages and the tray
+pub async fn refresh(app: &AppHandle, service: &str) -> Result<LastFetchTimes, ShellError> {
+    if service != ALL && !SERVICES.iter().any(|(name, _)| *name == service) {
+        return Err(ShellError::Invalid(format!("Unknown service: {}", service)));
+    }
+    let state = app.state::<SyncState>();
+    if !state.start(service) {
+        return Err(ShellError::Invalid(format!(
+            "Already refreshing {}",
+            service
+        )));
+    }
+    emit(app, service, "started", None, None);
+    tray::rebuild(app);
+
+    let result = fetch(service).await;
+    state.finish(service);
+    match &result {
+        Ok(times) => {
+            state.record(times);
+            emit(app, service, "finished", Some(times), None);
+            app.state::<Feed>().refresh();
+        }
+        Err(e) => {
+            println!("Refreshing {} failed: {}", service, e);
+            emit(app, service, "failed", None, Some(e.to_string()));
+        }
+    }
+    tray::rebuild(app);
+    result
+}
+
+async fn fetch(service: &str) -> Result<LastFetchTimes, ShellError> {
+    let client = reqwest::Client::new();
+    // The scheduler logs and swallows fetch errors, so the only sign of one is
+    // a last fetch time that didn't move
+    let before = fetch_last_times(&client).await?;
+
+    let response = client
+        .post(server::url(&format!("/api/fetch/{}", service)))
+        .send()
+        .await?;
+    let status = response.status();
+    let body: FetchResponse = response.json().await?;
+    let times = match (status.is_success(), body.last_fetch_times) {
+        (true, Some(times)) => times,
+        _ => {
+            return Err(ShellError::ServerUnavailable(
+                body.error
+                    .unwrap_or_else(|| format!("Server answered {}", status)),
+            ))
+        }
+    };
+
+    let failed = not_advanced(service, &before, &times);
+    if failed.is_empty() {
+        Ok(times)
+    } else {
+        Err(ShellError::ServerUnavailable(format!(
+            "Could not sync {}; see logs/server.log",
+            failed.join(", ")
+        )))
+    }
+}
+
+// Display names of the services whose last fetch time is unchanged. For ALL,
+// services that have never synced are taken to be not configured.
+fn not_advanced(
+    service: &str,
+    before: &LastFetchTimes,
+    after: &LastFetchTimes,
+) -> Vec<&'static str> {
+    SERVICES
+        .iter()
+        .filter(|(name, _)| service == ALL || service == *name)
+        .filter(|(name, _)| {
+            let old = before.get(*name).and_then(Option::as_deref);
+            let new = after.get(*name).and_then(Option::as_deref);
+            new == old && (service != ALL || new.is_some())
+        })
+        .map(|(_, display)| *display)
+        .collect()
+}
+
+// The last fetch times the scheduler reports in /health, so the tray knows
+// about scheduled fetches too
+pub async fn fetch_last_times(client: &reqwest::Client) -> Result<LastFetchTimes, reqwest::Error> {
+    #[derive(Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    struct Health {
+        last_fetch_times: LastFetchTimes,
+    }
+
+    let health: Health = client
+        .get(server::url("/health"))
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+    Ok(health.last_fetch_times)
+}
+
+fn emit(
+    app: &AppHandle,
+    service: &str,
+    status: &'static str,
+    last_fetch_times: Option<&LastFetchTimes>,
+    error: Option<String>,
+) {
+    let _ = app.emit_all(
+        "refresh-progress",
+        Progress {
+            service,
+            status,
+            last_fetch_times,
+            error,
+        },
+    );
+}
+
+// Tauri command behind the pages' per-service refresh buttons
+#[tauri::command]
+pub async fn refresh_service(
+    app: AppHandle,
+    service: String,
+) -> Result<LastFetchTimes, ShellError> {
+    refresh(&app, &service).await
+}
@@ -0,0 +1,271 @@
+use crate::external;
+use crate::feed::{DashboardData, Feed};
+use crate::geometry::{self, WindowState};
+use crate::instance::InstanceLock;
+use crate::palette;
+use crate::reminders;
+use crate::server::ServerProcess;
+use crate::sync::{self, SyncState};
+use chrono::{DateTime, Local, Utc};
+use tauri::{
+    AppHandle, CustomMenuItem, Manager, SystemTray, SystemTrayEvent, SystemTrayMenu,
+    SystemTrayMenuItem, SystemTraySubmenu,
+};
+
+const TOGGLE: &str = "toggle";
+const PALETTE: &str = "palette";
+const RESET_WINDOW: &str = "reset_window";
+const QUIT: &str = "quit";
+
+// Refresh items are "refresh:<service>", with "all" for everything
+const REFRESH: &str = "refresh:";
+
+// Items from the dashboard data open their link; the id carries the URL
+const OPEN: &str = "open:";
+const SHOW: &str = "show";
+
+const MAX_PRS: usize = 5;
+const MAX_EVENTS: usize = 3;
+const MAX_TASKS: usize = 5;
+
+// Longer titles are cut off so the menu stays a sensible width
+const MAX_LABEL: usize = 60;
+
+pub fn build() -> SystemTray {
+    SystemTray::new()
+        .with_menu(menu(false, None, None))
+        .with_tooltip("Mission Control")
+}
+
+// Rebuild the menu whenever fresh dashboard data arrives
+pub fn watch(app: &AppHandle) {
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let mut updates = app.state::<Feed>().subscribe();
+        while updates.changed().await.is_ok() {
+            updates.borrow_and_update();
+            rebuild(&app);
+        }
+    });
+}
+
+// Rebuild the menu from the latest data and sync state
+pub fn rebuild(app: &AppHandle) {
+    let data = app.state::<Feed>().latest();
+    let sync = app.state::<SyncState>();
+    let _ = app
+        .tray_handle()
+        .set_menu(menu(window_visible(app), data.as_deref(), Some(&sync)));
+}
+
+fn menu(visible: bool, data: Option<&DashboardData>, sync: Option<&SyncState>) -> SystemTrayMenu {
+    let mut services = SystemTrayMenu::new();
+    for (service, name) in sync::SERVICES {
+        services = services.add_item(refresh_item(sync, service, name));
+    }
+    let mut menu = SystemTrayMenu::new()
+        .add_item(CustomMenuItem::new(TOGGLE, toggle_title(visible)))
+        .add_item(CustomMenuItem::new(PALETTE, "Search…"))
+        .add_item(refresh_item(sync, sync::ALL, "All"))
+        .add_submenu(SystemTraySubmenu::new("Refresh Service", services));
+
+    if let Some(data) = data {
+        for (heading, items) in [
+            ("Review Requests", review_requests(data)),
+            ("Up Next", upcoming_events(data)),
+            ("Today's Tasks", todays_tasks(data)),
+        ] {
+            if items.is_empty() {
+                continue;
+            }
+            menu = menu
+                .add_native_item(SystemTrayMenuItem::Separator)
+                .add_item(CustomMenuItem::new(format!("heading:{}", heading), heading).disabled());
+            for (id, label) in items {
+                menu = menu.add_item(CustomMenuItem::new(id, label));
+            }
+        }
+    }
+
+    menu.add_native_item(SystemTrayMenuItem::Separator)
+        .add_item(CustomMenuItem::new(RESET_WINDOW, "Reset Window Position"))
+        .add_item(CustomMenuItem::new(QUIT, "Quit Mission Control"))
+}
+
+// Open PRs waiting on the user's review, most recently updated first
+fn review_requests(data: &DashboardData) -> Vec<(String, String)> {
+    data.github_prs
+        .iter()
+        .filter(|pr| pr.review_requested == Some(1))
+        .take(MAX_PRS)
+        .map(|pr| {
+            let repo = pr.repo_name.as_deref().unwrap_or_default();
+            let url = pr.html_url.clone().unwrap_or_else(|| {
+                let owner = pr.repo_owner.as_deref().unwrap_or_default();
+                format!("https://github.com/{}/{}/pull/{}", owner, repo, pr.number)
+            });
+            (
+                open_id(&url),
+                label(&format!("{}#{} {}", repo, pr.number, pr.title)),
+            )
+        })
+        .collect()
+}
+
+// The next few meetings; those with a video link open it, the rest show the
+// dashboard
+fn upcoming_events(data: &DashboardData) -> Vec<(String, String)> {
+    let now = Utc::now();
+    data.calendar
+        .iter()
+        .filter_map(|event| {
+            let start = DateTime::parse_from_rfc3339(&event.start_time).ok()?;
+            (start > now).then_some((start, event))
+        })
+        .take(MAX_EVENTS)
+        .map(|(start, event)| {
+            let link = reminders::meeting_link(event.location.as_deref().unwrap_or_default())
+                .or_else(|| {
+                    reminders::meeting_link(event.description.as_deref().unwrap_or_default())
+                });
+            let id = link.as_deref().map_or(SHOW.to_string(), open_id);
+            let time = start.with_timezone(&Local).format("%H:%M");
+            (id, label(&format!("{} {}", time, event.summary)))
+        })
+        .collect()
+}
+
+// Tasks due today, using the dashboard's UTC date. The dashboard's task list
+// leaves out overdue tasks, so there are none to show here.
+fn todays_tasks(data: &DashboardData) -> Vec<(String, String)> {
+    let today = Utc::now().format("%Y-%m-%d").to_string();
+    data.todoist_tasks
+        .iter()
+        .filter(|task| task.due_date.as_deref() == Some(today.as_str()))
+        .take(MAX_TASKS)
+        .map(|task| {
+            let url = task
+                .url
+                .clone()
+                .unwrap_or_else(|| format!("https://app.todoist.com/app/task/{}", task.task_id));
+            (open_id(&url), label(&task.content))
+        })
+        .collect()
+}
+
+// "Refresh GitHub (synced 3 min ago)", or disabled while it runs
+fn refresh_item(sync: Option<&SyncState>, service: &str, name: &str) -> CustomMenuItem {
+    let id = format!("{}{}", REFRESH, service);
+    if sync.is_some_and(|sync| sync.is_running(service)) {
+        return CustomMenuItem::new(id, format!("Refreshing {}…", name)).disabled();
+    }
+    let title = match sync.and_then(|sync| sync.synced_ago(service)) {
+        Some(ago) => format!("Refresh {} ({})", name, ago),
+        None => format!("Refresh {}", name),
+    };
+    CustomMenuItem::new(id, title)
+}
+
+fn open_id(url: &str) -> String {
+    format!("{}{}", OPEN, url)
+}
+
+fn label(text: &str) -> String {
+    let text = text.trim();
+    match text.char_indices().nth(MAX_LABEL) {
+        Some((at, _)) => format!("{}…", text[..at].trim_end()),
+        None => text.to_string(),
+    }
+}
+
+pub fn handle_event(app: &AppHandle, event: SystemTrayEvent) {
+    match event {
+        SystemTrayEvent::LeftClick { .. } => toggle_window(app),
+        SystemTrayEvent::MenuItemClick { id, .. } => match id.as_str() {
+            TOGGLE => toggle_window(app),
+            PALETTE => palette::show(app),
+            RESET_WINDOW => {
+                geometry::reset(app);
+                show_window(app);
+            }
+            QUIT => quit(app),
+            SHOW => show_window(app),
+            _ => {
+                if let Some(url) = id.strip_prefix(OPEN) {
+                    let _ = external::open(app, url);
+                } else if let Some(service) = id.strip_prefix(REFRESH) {
+                    refresh(app, service.to_string());
+                }
+            }
+        },
+        _ => {}
+    }
+}
+
+pub fn show_window(app: &AppHandle) {
+    if let Some(window) = app.get_window("main") {
+        let _ = window.show();
+        let _ = window.unminimize();
+        let _ = window.set_focus();
+    }
+    update_toggle(app, true);
+}
+
+pub fn hide_window(app: &AppHandle) {
+    if let Some(window) = app.get_window("main") {
+        let _ = window.hide();
+    }
+    update_toggle(app, false);
+}
+
+// Visible and focused, i.e. the user is looking at it
+pub fn dashboard_in_front(app: &AppHandle) -> bool {
+    app.get_window("main").is_some_and(|window| {
+        window.is_visible().unwrap_or(false) && window.is_focused().unwrap_or(false)
+    })
+}
+
+fn window_visible(app: &AppHandle) -> bool {
+    app.get_window("main")
+        .and_then(|window| window.is_visible().ok())
+        .unwrap_or(false)
+}
+
+fn toggle_window(app: &AppHandle) {
+    if window_visible(app) {
+        hide_window(app);
+    } else {
+        show_window(app);
+    }
+}
+
+fn toggle_title(visible: bool) -> &'static str {
+    if visible {
+        "Hide Mission Control"
+    } else {
+        "Show Mission Control"
+    }
+}
+
+fn update_toggle(app: &AppHandle, visible: bool) {
+    let _ = app
+        .tray_handle()
+        .get_item(TOGGLE)
+        .set_title(toggle_title(visible));
+}
+
+// Errors are logged and reported to the pages by sync::refresh
+fn refresh(app: &AppHandle, service: String) {
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let _ = sync::refresh(&app, &service).await;
+    });
+}
+
+// Shared quit path: stop the server cleanly before the process exits
+pub fn quit(app: &AppHandle) {
+    app.state::<WindowState>().flush();
+    app.state::<ServerProcess>().shutdown();
+    app.state::<InstanceLock>().release();
+    app.exit(0);
+}
@@ -0,0 +1,603 @@
+use crate::notify;
+use argon2::Argon2;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use chacha20poly1305::aead::{Aead, KeyInit};
+use chacha20poly1305::{ChaCha20Poly1305, Key, Nonce};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::env;
+use std::fs;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::Duration;
+
+// config.json fields that hold credentials, as dotted paths
+pub const SECRET_KEYS: [&str; 3] = [
+    "shortcut.apiToken",
+    "github.personalAccessToken",
+    "todoist.apiToken",
+];
+
+// Where the vault key comes from. desktop.json picks one; passphrases are read
+// from MISSION_CONTROL_VAULT_PASSPHRASE so they never touch disk.
+#[derive(Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum VaultBackend {
+    // Random key kept in the macOS Keychain, Windows Credential Manager or
+    // Secret Service
+    Keyring,
+    // Key derived from a passphrase with Argon2id
+    Passphrase,
+    // Unencrypted secrets.json, for tests and debugging only
+    Plain,
+}
+
+const KEYRING_SERVICE: &str = "mission-control";
+const KEYRING_USER: &str = "vault-key";
+pub const PASSPHRASE_VAR: &str = "MISSION_CONTROL_VAULT_PASSPHRASE";
+
+// How long the update endpoint waits on a connection before dropping it
+const UPDATE_TIMEOUT: Duration = Duration::from_secs(5);
+
+// On-disk layout of vault.json; everything but the header is encrypted
+#[derive(Serialize, Deserialize)]
+struct Sealed {
+    version: u32,
+    key: VaultBackend,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    salt: Option<String>,
+    nonce: String,
+    data: String,
+}
+
+trait SecretStore: Send + Sync {
+    fn load(&self) -> Result<BTreeMap<String, String>, String>;
+    fn save(&self, secrets: &BTreeMap<String, String>) -> Result<(), String>;
+}
+
+struct EncryptedFile {
+    path: PathBuf,
+    backend: VaultBackend,
+    key: [u8; 32],
+    // Only for passphrase keys; kept so the key derivation stays stable
+    salt: Option<[u8; 16]>,
+}
+
+impl EncryptedFile {
+    fn open(path: PathBuf, backend: VaultBackend) -> Result<Self, String> {
+        let existing: Option<Sealed> = match fs::read_to_string(&path) {
+            Ok(data) => Some(
+                serde_json::from_str(&data)
+                    .map_err(|e| format!("{} is corrupt: {}", path.display(), e))?,
+            ),
+            Err(_) => None,
+        };
+        if let Some(sealed) = &existing {
+            if sealed.key != backend {
+                return Err(format!(
+                    "{} was sealed with a different key provider than desktop.json asks for",
+                    path.display()
+                ));
+            }
+        }
+
+        let (key, salt) = match backend {
+            VaultBackend::Keyring => (keyring_key()?, None),
+            VaultBackend::Passphrase => {
+                let passphrase = env::var(PASSPHRASE_VAR)
+                    .map_err(|_| format!("{} is not set", PASSPHRASE_VAR))?;
+                let salt = match existing.as_ref().and_then(|s| s.salt.as_ref()) {
+                    Some(salt) => decode_array(salt)?,
+                    None => random()?,
+                };
+                let mut key = [0u8; 32];
+                Argon2::default()
+                    .hash_password_into(passphrase.as_bytes(), &salt, &mut key)
+                    .map_err(|e| e.to_string())?;
+                (key, Some(salt))
+            }
+            VaultBackend::Plain => unreachable!("plain vaults are not encrypted"),
+        };
+
+        Ok(EncryptedFile {
+            path,
+            backend,
+            key,
+            salt,
+        })
+    }
+
+    fn cipher(&self) -> ChaCha20Poly1305 {
+        ChaCha20Poly1305::new(Key::from_slice(&self.key))
+    }
+}
+
+impl SecretStore for EncryptedFile {
+    fn load(&self) -> Result<BTreeMap<String, String>, String> {
+        let data = match fs::read_to_string(&self.path) {
+            Ok(data) => data,
+            Err(_) => return Ok(BTreeMap::new()),
+        };
+        let sealed: Sealed = serde_json::from_str(&data).map_err(|e| e.to_string())?;
+        let nonce: [u8; 12] = decode_array(&sealed.nonce)?;
+        let ciphertext = BASE64.decode(&sealed.data).map_err(|e| e.to_string())?;
+
+        let plaintext = self
+            .cipher()
+            .decrypt(Nonce::from_slice(&nonce), ciphertext.as_ref())
+            .map_err(|_| "Could not decrypt the vault; wrong key or passphrase".to_string())?;
+        serde_json::from_slice(&plaintext).map_err(|e| e.to_string())
+    }
+
+    fn save(&self, secrets: &BTreeMap<String, String>) -> Result<(), String> {
+        let nonce: [u8; 12] = random()?;
+        let plaintext = serde_json::to_vec(secrets).map_err(|e| e.to_string())?;
+        let ciphertext = self
+            .cipher()
+            .encrypt(Nonce::from_slice(&nonce), plaintext.as_ref())
+            .map_err(|_| "Could not encrypt the vault".to_string())?;
+
+        let sealed = Sealed {
+            version: 1,
+            key: self.backend,
+            salt: self.salt.map(|salt| BASE64.encode(salt)),
+            nonce: BASE64.encode(nonce),
+            data: BASE64.encode(ciphertext),
+        };
+        let data = serde_json::to_string_pretty(&sealed).map_err(|e| e.to_string())?;
+        write_private(&self.path, data.as_bytes())
+    }
+}
+
+struct PlainFile {
+    path: PathBuf,
+}
+
+impl SecretStore for PlainFile {
+    fn load(&self) -> Result<BTreeMap<String, String>, String> {
+        match fs::read_to_string(&self.path) {
+            Ok(data) => serde_json::from_str(&data).map_err(|e| e.to_string()),
+            Err(_) => Ok(BTreeMap::new()),
+        }
+    }
+
+    fn save(&self, secrets: &BTreeMap<String, String>) -> Result<(), String> {
+        let data = serde_json::to_string_pretty(secrets).map_err(|e| e.to_string())?;
+        write_private(&self.path, data.as_bytes())
+    }
+}
+
+// Holds the API tokens that used to sit in config.json and hands them to the
+// server when it is spawned
+pub struct Vault {
+    store: Box<dyn SecretStore>,
+    secrets: Mutex<BTreeMap<String, String>>,
+    // Bearer token the server uses to send back tokens changed in settings
+    update_token: String,
+    update_port: OnceLock<u16>,
+}
+
+// Open the vault, start its update endpoint and hand the tokens to the server.
+// Without a usable key provider the server keeps reading config.json as before.
+pub fn start(config_dir: &Path, config_file: &Path, backend: VaultBackend) -> Option<Arc<Vault>> {
+    let vault = match Vault::open(config_dir, backend) {
+        Ok(vault) => Arc::new(vault),
+        Err(e) => {
+            println!(
+                "Secrets vault unavailable, tokens stay in config.json: {}",
+                e
+            );
+            // An earlier launch already emptied the tokens in config.json
+            if store_path(config_dir, backend).is_file() {
+                notify::show(
+                    "API tokens unavailable".to_string(),
+                    format!(
+                        "Could not open the token vault: {}. GitHub, Shortcut and Todoist will look unconfigured until it opens again.",
+                        e
+                    ),
+                    Vec::new(),
+                    |_| {},
+                );
+            }
+            return None;
+        }
+    };
+    match serve(&vault) {
+        Ok(port) => {
+            let _ = vault.update_port.set(port);
+        }
+        Err(e) => {
+            println!("Could not start the vault update endpoint: {}", e);
+            return None;
+        }
+    }
+    vault.refresh(config_file);
+    Some(vault)
+}
+
+impl Vault {
+    fn open(config_dir: &Path, backend: VaultBackend) -> Result<Vault, String> {
+        let path = store_path(config_dir, backend);
+        let store: Box<dyn SecretStore> = match backend {
+            VaultBackend::Plain => Box::new(PlainFile { path }),
+            _ => Box::new(EncryptedFile::open(path, backend)?),
+        };
+        let secrets = store.load()?;
+
+        Ok(Vault {
+            store,
+            secrets: Mutex::new(secrets),
+            update_token: BASE64.encode(random::<32>()?),
+            update_port: OnceLock::new(),
+        })
+    }
+
+    // Pick up any plaintext tokens, e.g. after an import
+    pub fn refresh(&self, config_file: &Path) {
+        if let Err(e) = self.migrate_config(config_file) {
+            println!("Could not move tokens out of {:?}: {}", config_file, e);
+        }
+    }
+
+    // The stored tokens, keyed like SECRET_KEYS
+    pub fn secrets(&self) -> BTreeMap<String, String> {
+        self.secrets.lock().unwrap().clone()
+    }
+
+    // Store new token values; an empty value clears one
+    fn update(&self, incoming: BTreeMap<String, String>) -> Result<(), String> {
+        let mut secrets = self.secrets.lock().unwrap();
+        let mut updated = secrets.clone();
+        for (key, value) in incoming {
+            if !SECRET_KEYS.contains(&key.as_str()) {
+                return Err(format!("{} is not a secret", key));
+            }
+            if value.is_empty() {
+                updated.remove(&key);
+            } else {
+                updated.insert(key, value);
+            }
+        }
+
+        if updated != *secrets {
+            self.store.save(&updated)?;
+            *secrets = updated;
+        }
+        Ok(())
+    }
+
+    // Move plaintext tokens out of config.json, leaving empty strings behind.
+    // Runs on every start so files written by older builds or an import are
+    // cleaned up too.
+    fn migrate_config(&self, config_file: &Path) -> Result<usize, String> {
+        let mut config: Value = match fs::read_to_string(config_file) {
+            Ok(data) => serde_json::from_str(&data).map_err(|e| e.to_string())?,
+            Err(_) => return Ok(0),
+        };
+
+        let mut found = BTreeMap::new();
+        for key in SECRET_KEYS {
+            let mut path = key.split('.');
+            let (section, field) = (path.next().unwrap(), path.next().unwrap());
+            if let Some(Value::String(value)) =
+                config.get_mut(section).and_then(|s| s.get_mut(field))
+            {
+                if !value.is_empty() {
+                    found.insert(key.to_string(), std::mem::take(value));
+                }
+            }
+        }
+        if found.is_empty() {
+            return Ok(0);
+        }
+
+        // Secure the tokens before removing them from the config
+        let count = found.len();
+        self.update(found)?;
+        let data = serde_json::to_string_pretty(&config).map_err(|e| e.to_string())?;
+        write_private(config_file, data.as_bytes())?;
+        println!(
+            "Moved {} token(s) from {:?} into the vault",
+            count, config_file
+        );
+        Ok(count)
+    }
+
+    // Environment for a server spawn: the current tokens and how to send back
+    // changes. Only the server's Command gets these, never the shell's own
+    // environment, so other children don't see them.
+    pub fn server_env(&self) -> Vec<(&'static str, String)> {
+        let Some(port) = self.update_port.get() else {
+            return Vec::new();
+        };
+        let secrets = serde_json::to_string(&*self.secrets.lock().unwrap()).unwrap_or_default();
+        vec![
+            ("MISSION_CONTROL_SECRETS", secrets),
+            (
+                "MISSION_CONTROL_VAULT_URL",
+                format!("http://127.0.0.1:{}/secrets", port),
+            ),
+            ("MISSION_CONTROL_VAULT_TOKEN", self.update_token.clone()),
+        ]
+    }
+}
+
+fn store_path(config_dir: &Path, backend: VaultBackend) -> PathBuf {
+    match backend {
+        VaultBackend::Plain => config_dir.join("secrets.json"),
+        _ => config_dir.join("vault.json"),
+    }
+}
+
+// Accept token changes from server.js, which no longer writes them to
+// config.json. Returns the loopback port to hand to the server.
+fn serve(vault: &Arc<Vault>) -> Result<u16, String> {
+    let listener = TcpListener::bind("127.0.0.1:0").map_err(|e| e.to_string())?;
+    let port = listener.local_addr().map_err(|e| e.to_string())?.port();
+    let vault = Arc::clone(vault);
+
+    std::thread::spawn(move || {
+        for stream in listener.incoming().flatten() {
+            let status = match handle_update(&vault, &stream) {
+                Ok(()) => "204 No Content",
+                Err(status) => status,
+            };
+            let mut stream = stream;
+            let _ = write!(stream, "HTTP/1.1 {}\r\nContent-Length: 0\r\n\r\n", status);
+        }
+    });
+    Ok(port)
+}
+
+fn handle_update(vault: &Vault, stream: &TcpStream) -> Result<(), &'static str> {
+    // Connections are handled one at a time, so a client that goes quiet
+    // mustn't hold up the next update
+    stream
+        .set_read_timeout(Some(UPDATE_TIMEOUT))
+        .map_err(|_| "500 Internal Server Error")?;
+    let mut reader = BufReader::new(stream);
+    let mut request_line = String::new();
+    reader
+        .read_line(&mut request_line)
+        .map_err(|_| "400 Bad Request")?;
+    if !request_line.starts_with("POST /secrets ") {
+        return Err("404 Not Found");
+    }
+
+    let mut length = 0;
+    let mut authorized = false;
+    loop {
+        let mut header = String::new();
+        reader
+            .read_line(&mut header)
+            .map_err(|_| "400 Bad Request")?;
+        let header = header.trim_end();
+        if header.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = header.split_once(':') {
+            let value = value.trim();
+            match name.trim().to_ascii_lowercase().as_str() {
+                "content-length" => length = value.parse().map_err(|_| "400 Bad Request")?,
+                "authorization" => {
+                    authorized = value.strip_prefix("Bearer ") == Some(vault.update_token.as_str())
+                }
+                _ => {}
+            }
+        }
+    }
+    if !authorized {
+        println!("Rejected unauthenticated vault update");
+        return Err("401 Unauthorized");
+    }
+
+    let mut body = vec![0; length];
+    reader
+        .read_exact(&mut body)
+        .map_err(|_| "400 Bad Request")?;
+    let secrets = serde_json::from_slice(&body).map_err(|_| "400 Bad Request")?;
+    // A crash restart picks up the new tokens through server_env
+    vault.update(secrets).map_err(|e| {
+        println!("Could not update vault: {}", e);
+        "500 Internal Server Error"
+    })
+}
+
+// The key for keyring-backed vaults, created on first use
+fn keyring_key() -> Result<[u8; 32], String> {
+    let entry = keyring::Entry::new(KEYRING_SERVICE, KEYRING_USER).map_err(|e| e.to_string())?;
+    match entry.get_password() {
+        Ok(encoded) => decode_array(&encoded),
+        Err(keyring::Error::NoEntry) => {
+            let key = random::<32>()?;
+            entry
+                .set_password(&BASE64.encode(key))
+                .map_err(|e| e.to_string())?;
+            Ok(key)
+        }
+        Err(e) => Err(format!("OS key store unavailable: {}", e)),
+    }
+}
+
+fn random<const N: usize>() -> Result<[u8; N], String> {
+    let mut bytes = [0u8; N];
+    getrandom::getrandom(&mut bytes).map_err(|e| e.to_string())?;
+    Ok(bytes)
+}
+
+fn decode_array<const N: usize>(encoded: &str) -> Result<[u8; N], String> {
+    BASE64
+        .decode(encoded)
+        .map_err(|e| e.to_string())?
+        .try_into()
+        .map_err(|_| "Vault key material has the wrong length".to_string())
+}
+
+// Write via a temporary file so a crash never leaves half a file, readable
+// only by the current user
+fn write_private(path: &Path, data: &[u8]) -> Result<(), String> {
+    let mut temp = path.as_os_str().to_os_string();
+    temp.push(".tmp");
+    let temp = PathBuf::from(temp);
+
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    let mut file = options.open(&temp).map_err(|e| e.to_string())?;
+    file.write_all(data).map_err(|e| e.to_string())?;
+    file.sync_all().map_err(|e| e.to_string())?;
+    fs::rename(&temp, path).map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // A fresh directory per test, removed when dropped
+    struct TempDir(PathBuf);
+
+    impl TempDir {
+        fn new(name: &str) -> Self {
+            let dir = env::temp_dir().join(format!(
+                "mission-control-vault-{}-{}",
+                std::process::id(),
+                name
+            ));
+            let _ = fs::remove_dir_all(&dir);
+            fs::create_dir_all(&dir).unwrap();
+            TempDir(dir)
+        }
+    }
+
+    impl Drop for TempDir {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.0);
+        }
+    }
+
+    fn secrets() -> BTreeMap<String, String> {
+        BTreeMap::from([
+            (
+                "github.personalAccessToken".to_string(),
+                "ghp_secret".to_string(),
+            ),
+            ("todoist.apiToken".to_string(), "todoist-secret".to_string()),
+        ])
+    }
+
+    fn encrypted(path: PathBuf, key: [u8; 32]) -> EncryptedFile {
+        EncryptedFile {
+            path,
+            backend: VaultBackend::Passphrase,
+            key,
+            salt: Some([7; 16]),
+        }
+    }
+
+    #[test]
+    fn plain_file_round_trip() {
+        let dir = TempDir::new("plain");
+        let store = PlainFile {
+            path: dir.0.join("secrets.json"),
+        };
+        assert!(store.load().unwrap().is_empty());
+
+        store.save(&secrets()).unwrap();
+        assert_eq!(store.load().unwrap(), secrets());
+    }
+
+    #[test]
+    fn encrypted_file_round_trip() {
+        let dir = TempDir::new("encrypted");
+        let path = dir.0.join("vault.json");
+        let store = encrypted(path.clone(), [1; 32]);
+
+        store.save(&secrets()).unwrap();
+        let on_disk = fs::read_to_string(&path).unwrap();
+        assert!(!on_disk.contains("ghp_secret"));
+        assert!(!on_disk.contains("todoist-secret"));
+        assert_eq!(store.load().unwrap(), secrets());
+    }
+
+    #[test]
+    fn encrypted_file_rejects_the_wrong_key() {
+        let dir = TempDir::new("wrong-key");
+        let path = dir.0.join("vault.json");
+        encrypted(path.clone(), [1; 32]).save(&secrets()).unwrap();
+
+        assert!(encrypted(path, [2; 32]).load().is_err());
+    }
+
+    #[test]
+    fn update_only_accepts_known_secrets() {
+        let dir = TempDir::new("update");
+        let vault = Vault::open(&dir.0, VaultBackend::Plain).unwrap();
+
+        vault.update(secrets()).unwrap();
+        let cleared = BTreeMap::from([("todoist.apiToken".to_string(), String::new())]);
+        vault.update(cleared).unwrap();
+        let unknown = BTreeMap::from([("server.port".to_string(), "1".to_string())]);
+        assert!(vault.update(unknown).is_err());
+
+        let reopened = Vault::open(&dir.0, VaultBackend::Plain).unwrap();
+        let stored = reopened.secrets.lock().unwrap().clone();
+        assert_eq!(
+            stored,
+            BTreeMap::from([(
+                "github.personalAccessToken".to_string(),
+                "ghp_secret".to_string()
+            )])
+        );
+    }
+
+    #[test]
+    fn migrate_moves_tokens_out_of_the_config() {
+        let dir = TempDir::new("migrate");
+        let config = dir.0.join("config.json");
+        fs::write(
+            &config,
+            r#"{"github": {"personalAccessToken": "ghp_secret", "username": "me"}}"#,
+        )
+        .unwrap();
+        let vault = Vault::open(&dir.0, VaultBackend::Plain).unwrap();
+
+        assert_eq!(vault.migrate_config(&config).unwrap(), 1);
+        let left: Value = serde_json::from_str(&fs::read_to_string(&config).unwrap()).unwrap();
+        assert_eq!(left["github"]["personalAccessToken"], "");
+        assert_eq!(left["github"]["username"], "me");
+        assert_eq!(
+            vault
+                .secrets
+                .lock()
+                .unwrap()
+                .get("github.personalAccessToken"),
+            Some(&"ghp_secret".to_string())
+        );
+    }
+
+    #[test]
+    fn server_env_carries_the_current_tokens() {
+        let dir = TempDir::new("server-env");
+        let vault = Vault::open(&dir.0, VaultBackend::Plain).unwrap();
+        assert!(vault.server_env().is_empty());
+
+        vault.update_port.set(4242).unwrap();
+        vault.update(secrets()).unwrap();
+        let env: BTreeMap<_, _> = vault.server_env().into_iter().collect();
+        let sent: BTreeMap<String, String> =
+            serde_json::from_str(&env["MISSION_CONTROL_SECRETS"]).unwrap();
+        assert_eq!(sent, secrets());
+        assert_eq!(
+            env["MISSION_CONTROL_VAULT_URL"],
+            "http://127.0.0.1:4242/secrets"
+        );
+    }
+}
@@ -30,6 +30,24 @@ function getConfigPath() {
 const CONFIG_PATH = getConfigPath();
 console.log('Config path:', CONFIG_PATH);
 
+// The desktop app keeps API tokens in its encrypted vault. It passes them in
+// at startup and takes changes back over an authenticated loopback endpoint,
+// so they are never written to config.json.
+const SECRET_KEYS = ['shortcut.apiToken', 'github.personalAccessToken', 'todoist.apiToken'];
+const VAULT_SECRETS = process.env.MISSION_CONTROL_SECRETS
+  ? JSON.parse(process.env.MISSION_CONTROL_SECRETS)
+  : null;
+
+function getSecret(config, key) {
+  const [section, field] = key.split('.');
+  return (config[section] && config[section][field]) || '';
+}
+
+function setSecret(config, key, value) {
+  const [section, field] = key.split('.');
+  config[section] = { ...config[section], [field]: value };
+}
+
 // Default configuration
 const defaultConfig = {
   isConfigured: false,
@@ -54,21 +72,27 @@ const defaultConfig = {
 class ConfigManager {
   constructor() {
     this.config = this.loadConfig();
+    // Tokens changed in settings that the vault hasn't confirmed yet
+    this.unsavedSecrets = {};
   }
 
   /**
    * Load configuration from file or return defaults
    */
   loadConfig() {
+    let config = { ...defaultConfig };
     try {
       if (fs.existsSync(CONFIG_PATH)) {
         const data = fs.readFileSync(CONFIG_PATH, 'utf8');
-        return { ...defaultConfig, ...JSON.parse(data) };
+        config = { ...defaultConfig, ...JSON.parse(data) };
       }
     } catch (error) {
       console.error('Error loading config:', error.message);
     }
-    return { ...defaultConfig };
+    if (VAULT_SECRETS) {
+      SECRET_KEYS.forEach(key => setSecret(config, key, VAULT_SECRETS[key] || ''));
+    }
+    return config;
   }
 
   /**
@@ -76,8 +100,27 @@ class ConfigManager {
    */
   saveConfig(newConfig) {
     try {
+      const previous = this.config;
       this.config = { ...this.config, ...newConfig };
-      fs.writeFileSync(CONFIG_PATH, JSON.stringify(this.config, null, 2));
+
+      if (!VAULT_SECRETS) {
+        fs.writeFileSync(CONFIG_PATH, JSON.stringify(this.config, null, 2));
+        return true;
+      }
+
+      const changed = {};
+      SECRET_KEYS.forEach(key => {
+        if (getSecret(this.config, key) !== getSecret(previous, key)) {
+          changed[key] = getSecret(this.config, key);
+        }
+      });
+      // Changed tokens stay in config.json until the vault has them, so a
+      // failed hand-off doesn't lose them; the vault picks them up next launch
+      Object.assign(this.unsavedSecrets, changed);
+      this.writeWithoutSecrets();
+      if (Object.keys(changed).length > 0) {
+        this.sendToVault(changed);
+      }
       return true;
     } catch (error) {
       console.error('Error saving config:', error.message);
@@ -85,6 +128,43 @@ class ConfigManager {
     }
   }
 
+  /**
+   * Write config.json with only the tokens the vault doesn't have yet
+   */
+  writeWithoutSecrets() {
+    const onDisk = JSON.parse(JSON.stringify(this.config));
+    SECRET_KEYS.forEach(key => setSecret(onDisk, key, this.unsavedSecrets[key] || ''));
+    fs.writeFileSync(CONFIG_PATH, JSON.stringify(onDisk, null, 2));
+  }
+
+  /**
+   * Hand changed tokens to the desktop app's vault, then drop them from config.json
+   */
+  async sendToVault(secrets) {
+    try {
+      const response = await fetch(process.env.MISSION_CONTROL_VAULT_URL, {
+        method: 'POST',
+        headers: {
+          'Authorization': `Bearer ${process.env.MISSION_CONTROL_VAULT_TOKEN}`,
+          'Content-Type': 'application/json'
+        },
+        body: JSON.stringify(secrets)
+      });
+      if (!response.ok) {
+        throw new Error(`vault responded ${response.status}`);
+      }
+      // A later save may have changed a token again while this was in flight
+      Object.entries(secrets).forEach(([key, value]) => {
+        if (this.unsavedSecrets[key] === value) {
+          delete this.unsavedSecrets[key];
+        }
+      });
+      this.writeWithoutSecrets();
+    } catch (error) {
+      console.error('Error saving tokens to the vault, keeping them in config.json:', error.message);
+    }
+  }
+
   /**
    * Get current configuration
    */
@@ -8,8 +8,9 @@ const Database = require('better-sqlite3');
 const path = require('path');
 const fs = require('fs');
 
-const DB_DIR = path.join(__dirname, '../../data');
-const DB_PATH = path.join(DB_DIR, 'mission-control.db');
+// The desktop app passes its own data directory; Docker and dev use ./data
+const DB_DIR = process.env.MISSION_CONTROL_DATA_DIR || path.join(__dirname, '../../data');
+const DB_PATH = process.env.MISSION_CONTROL_DB || path.join(DB_DIR, 'mission-control.db');
 
 class DatabaseManager {
   constructor() {
@@ -21,8 +22,9 @@ class DatabaseManager {
    */
   initDatabase() {
     // Ensure data directory exists
-    if (!fs.existsSync(DB_DIR)) {
-      fs.mkdirSync(DB_DIR, { recursive: true });
+    const dbDir = path.dirname(DB_PATH);
+    if (!fs.existsSync(dbDir)) {
+      fs.mkdirSync(dbDir, { recursive: true });
     }
 
     // Open database connection
@@ -491,7 +493,14 @@ class DatabaseManager {
           CASE WHEN due_date = date('now') THEN 0 ELSE 1 END,
           priority DESC,
           created_at ASC
-      `).all()
+      `).all(),
+
+      // Number of incomplete Todoist tasks due before today (desktop app badge)
+      todoistOverdueCount: this.db.prepare(`
+        SELECT COUNT(*) AS count FROM todoist_tasks 
+        WHERE is_completed = 0 
+          AND due_date < date('now')
+      `).get().count
     };
   }
 
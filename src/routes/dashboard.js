@@ -319,7 +319,7 @@ function createDashboardRouter(scheduler) {
         return res.status(400).json({ success: false, error: 'Todoist not configured' });
       }
 
-      const { content, due_date, priority } = req.body;
+      const { content, due_date, due_string, priority } = req.body;
       
       if (!content) {
         return res.status(400).json({ success: false, error: 'Task content is required' });
@@ -329,6 +329,7 @@ function createDashboardRouter(scheduler) {
       
       const options = {};
       if (due_date) options.due_date = due_date;
+      if (due_string) options.due_string = due_string;
       if (priority) options.priority = parseInt(priority);
       
       const task = await todoist.createTask(content, options);
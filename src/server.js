@@ -12,6 +12,8 @@ const Scheduler = require('./scheduler');
 
 const app = express();
 const PORT = process.env.PORT || 1337;
+// The desktop app sets HOST to keep the server on loopback; Docker listens on all interfaces
+const HOST = process.env.HOST;
 
 // Middleware
 app.use(bodyParser.urlencoded({ extended: true }));
@@ -92,7 +94,7 @@ app.get('/health', (req, res) => {
 });
 
 // Start server
-app.listen(PORT, () => {
+const onListening = () => {
   console.log(`🚀 Mission Control running on port ${PORT}`);
   console.log(`📊 Dashboard: http://localhost:${PORT}`);
   
@@ -103,21 +105,35 @@ app.listen(PORT, () => {
     // Start scheduler if configured
     scheduler.start();
   }
-});
+};
 
-// Handle graceful shutdown
-process.on('SIGTERM', () => {
-  console.log('SIGTERM received, shutting down gracefully');
-  scheduler.stop();
-  db.close();
-  process.exit(0);
-});
+if (HOST) {
+  app.listen(PORT, HOST, onListening);
+} else {
+  app.listen(PORT, onListening);
+}
 
-process.on('SIGINT', () => {
-  console.log('SIGINT received, shutting down gracefully');
+// Handle graceful shutdown
+const shutdown = (reason) => {
+  console.log(`${reason} received, shutting down gracefully`);
   scheduler.stop();
   db.close();
   process.exit(0);
-});
+};
+
+process.on('SIGTERM', () => shutdown('SIGTERM'));
+process.on('SIGINT', () => shutdown('SIGINT'));
+
+// Windows has no SIGTERM for a windowless process, so the desktop app writes
+// "shutdown" to stdin instead
+if (process.env.MISSION_CONTROL_STDIN_SHUTDOWN) {
+  require('readline')
+    .createInterface({ input: process.stdin })
+    .on('line', (line) => {
+      if (line.trim() === 'shutdown') {
+        shutdown('Shutdown request');
+      }
+    });
+}
 
 module.exports = app;